@@ -4,6 +4,10 @@ use rustc_version::{ version_meta, Channel };
 
 fn main()
 {
+	// Declare the cfg flags we set so rustc doesn't warn about unexpected cfgs.
+	//
+	println!( "cargo:rustc-check-cfg=cfg(stable, beta, nightly, rustc_dev)" );
+
 	// Set cfg flags depending on release channel
 	//
 	match version_meta().unwrap().channel
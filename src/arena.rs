@@ -0,0 +1,104 @@
+use
+{
+	crate :: { IoStream                 } ,
+	std   :: { io::IoSlice              } ,
+	bytes :: { Bytes, BytesMut, BufMut  } ,
+};
+
+
+
+#[ cfg_attr( nightly, doc(cfg( feature = "bytes" )) ) ]
+//
+impl<St> IoStream<St, Bytes>
+where
+
+	St: Unpin,
+
+{
+	/// Create the items for the sink from a reusable [`BytesMut`] arena instead of allocating a new
+	/// `Vec<u8>` for every write. Every item is split off the arena and frozen, so once the sink has
+	/// dropped the items, the memory is reclaimed for the next ones. `capacity` is the initial size
+	/// of the arena.
+	///
+	/// This is most effective if the sink drops items quickly, like when they are written straight
+	/// to a socket.
+	///
+	/// It also makes [`IoStream::poll_next_item`] return the rest of a partially read item as a slice
+	/// of the original `Bytes` instead of a copy. Without it, that rest is copied, so call this with
+	/// a `capacity` of 0 to get slices on a stream that is only read from.
+	///
+	/// This requires the `bytes` feature.
+	//
+	pub fn with_arena( mut self, capacity: usize ) -> Self
+	{
+		self.arena = Some( Arena
+		{
+			buf   : BytesMut::with_capacity( capacity ),
+			freeze: |bytes| bytes,
+			thaw  : |item | item ,
+		});
+
+		self
+	}
+}
+
+
+
+// Creates items from a reusable BytesMut. It can only be created in with_arena where I is Bytes,
+// but it lives in IoStream<St, I>, so we keep functions to convert between Bytes and I.
+//
+pub(crate) struct Arena<I>
+{
+	buf   : BytesMut       ,
+	freeze: fn(Bytes) -> I ,
+	thaw  : fn(I) -> Bytes ,
+}
+
+
+impl<I> Arena<I>
+{
+	// Create an item from the first `take` bytes in bufs.
+	//
+	pub(crate) fn make_item( &mut self, bufs: &[ IoSlice<'_> ], take: usize ) -> I
+	{
+		// If all items previously split off have been dropped, this reclaims their memory.
+		//
+		self.buf.reserve( take );
+
+		let mut left = take;
+
+		for buf in bufs
+		{
+			let n = buf.len().min( left );
+
+			self.buf.put_slice( &buf[..n] );
+			left -= n;
+
+			if left == 0 { break }
+		}
+
+		( self.freeze )( self.buf.split().freeze() )
+	}
+
+
+	// Zero copy slice of the item from `pos` to the end.
+	//
+	pub(crate) fn slice( &self, item: I, pos: usize ) -> I
+	{
+		( self.freeze )( ( self.thaw )( item ).slice( pos.. ) )
+	}
+
+
+	// An arena with the same conversions, but without memory of its own, for the read half of a
+	// split IoStream which only needs slice.
+	//
+	pub(crate) fn fork( &self ) -> Self
+	{
+		Self
+		{
+			buf   : BytesMut::new() ,
+			freeze: self.freeze     ,
+			thaw  : self.thaw       ,
+		}
+	}
+}
@@ -0,0 +1,105 @@
+use
+{
+	crate   :: { IoStream                                                } ,
+	std     :: { io                                                      } ,
+	std     :: { pin::Pin, task::{ Poll, Context }                       } ,
+	futures :: { Stream, Sink, channel::mpsc::{ self, Sender, Receiver } } ,
+};
+
+
+
+impl IoStream< Duplex, Vec<u8> >
+{
+	/// Create two connected IoStreams, backed by bounded in-memory channels of `Vec<u8>`. Every
+	/// item written to one end is read from the other, so item boundaries are preserved just like
+	/// with a message based network transport. Useful for tests and in-process protocol stacks.
+	///
+	/// `capacity` is the buffer size of the channel in each direction. As with [`mpsc::channel`],
+	/// one extra item can be buffered per sender, so at most `capacity + 1` items can be in flight
+	/// in each direction before writes return `Pending`.
+	///
+	/// When one end is closed or dropped, the other end will read EOF after the buffered items and
+	/// writes to it will fail with [`io::ErrorKind::NotConnected`].
+	//
+	pub fn pair( capacity: usize ) -> (Self, Self)
+	{
+		let (a_tx, b_rx) = mpsc::channel( capacity );
+		let (b_tx, a_rx) = mpsc::channel( capacity );
+
+		let a = Duplex{ rx: a_rx, tx: a_tx };
+		let b = Duplex{ rx: b_rx, tx: b_tx };
+
+		( IoStream::new(a), IoStream::new(b) )
+	}
+}
+
+
+
+/// One end of an in-memory transport created by [`IoStream::pair`]. Implements [`Stream`] and
+/// [`Sink`] over `Vec<u8>`.
+//
+#[ derive( Debug ) ]
+//
+pub struct Duplex
+{
+	rx: Receiver< Vec<u8> >,
+	tx: Sender  < Vec<u8> >,
+}
+
+
+
+impl Stream for Duplex
+{
+	type Item = io::Result< Vec<u8> >;
+
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Option<Self::Item> >
+	{
+		Pin::new( &mut self.rx ).poll_next( cx ).map( |item| item.map( Ok ) )
+	}
+
+
+	fn size_hint( &self ) -> (usize, Option<usize>)
+	{
+		self.rx.size_hint()
+	}
+}
+
+
+
+impl Sink< Vec<u8> > for Duplex
+{
+	type Error = io::Error;
+
+	fn poll_ready( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Result<(), Self::Error> >
+	{
+		Pin::new( &mut self.tx ).poll_ready( cx ).map_err( to_io )
+	}
+
+
+	fn start_send( mut self: Pin<&mut Self>, item: Vec<u8> ) -> Result<(), Self::Error>
+	{
+		Pin::new( &mut self.tx ).start_send( item ).map_err( to_io )
+	}
+
+
+	fn poll_flush( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Result<(), Self::Error> >
+	{
+		Pin::new( &mut self.tx ).poll_flush( cx ).map_err( to_io )
+	}
+
+
+	fn poll_close( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Result<(), Self::Error> >
+	{
+		Pin::new( &mut self.tx ).poll_close( cx ).map_err( to_io )
+	}
+}
+
+
+
+// The only way a send on a bounded channel can fail after poll_ready returned Ready is that the
+// other end went away.
+//
+fn to_io( err: mpsc::SendError ) -> io::Error
+{
+	io::Error::new( io::ErrorKind::NotConnected, err )
+}
@@ -0,0 +1,113 @@
+use
+{
+	crate   :: { IoStream                                                                } ,
+	std     :: { io, pin::Pin, task::{ Poll, Context }, sync::{ Arc, Mutex, PoisonError } } ,
+	futures :: { Sink, FutureExt, task::noop_waker                                       } ,
+	pharos  :: { Pharos, Observable, ObserveConfig, Events, PharErr, ErrorKind           } ,
+};
+
+
+
+/// Events about what happens inside an [`IoStream`], obtained from [`IoStream::observe_io`].
+/// Requires the `map_pharos` feature.
+//
+#[ derive( Debug, Clone, PartialEq, Eq ) ]
+//
+#[ non_exhaustive ]
+//
+pub enum IoEvent
+{
+	/// An item of `len` bytes was received from the stream.
+	//
+	ItemReceived{ /** The size of the item in bytes. */ len: usize },
+
+	/// The stream returned an error while a read already had data to return. The error was
+	/// buffered and will be returned from the next read.
+	//
+	ReadErrorBuffered{ /** The kind of the buffered error. */ kind: io::ErrorKind },
+
+	/// The sink returned an error after the data of a write was already taken, typically from an
+	/// implicit flush. The error was stored and will be returned from the next write.
+	//
+	WriteErrorDeferred{ /** The kind of the stored error. */ kind: io::ErrorKind },
+
+	/// The stream has ended.
+	//
+	Eof,
+
+	/// A write was bigger than the maximum item size, so only `taken` of its `len` bytes were
+	/// sent as one item.
+	//
+	WriteSplit
+	{
+		/// The number of bytes accepted from the write.
+		//
+		taken: usize,
+
+		/// The number of bytes in the write.
+		//
+		len: usize,
+	},
+
+	/// The write buffer, holding data of one or more writes, is sent to the sink as one item of
+	/// `len` bytes.
+	//
+	WriteCoalesced{ /** The size of the item in bytes. */ len: usize },
+}
+
+
+
+// The observers are shared between the halves of a split IoStream, so they keep receiving the events
+// of both sides. It's only created when someone observes.
+//
+pub(crate) type SharedPharos = Option< Arc< Mutex< Pharos<IoEvent> > > >;
+
+
+
+impl<St, I> IoStream<St, I>
+where
+
+	St: Unpin,
+
+{
+	/// Observe events from the IoStream itself, like items being received, errors being buffered or
+	/// writes being split. This works whether or not the inner transport is observable. Events from
+	/// the inner transport are available through the [`Observable`] impl, which forwards to it.
+	///
+	/// Events are emitted from within the poll methods, which can't wait for observers. When an
+	/// observer with a bounded channel is full, the event is dropped for all observers, so prefer
+	/// [`Channel::Unbounded`](pharos::Channel::Unbounded), the default. Observers registered
+	/// before [`IoStream::split`] receive the events of both halves.
+	///
+	/// Requires the `map_pharos` feature.
+	//
+	pub fn observe_io( &mut self, options: ObserveConfig<IoEvent> ) -> Result< Events<IoEvent>, PharErr >
+	{
+		let     pharos = self.pharos.get_or_insert_with( || Arc::new( Mutex::new( Pharos::new(1) ) ) );
+		let mut pharos = pharos.lock().unwrap_or_else( PoisonError::into_inner );
+
+		// Pharos doesn't wait for anything while registering an observer.
+		//
+		pharos.observe( options ).now_or_never()
+
+			.unwrap_or_else( || Err( ErrorKind::Closed.into() ) )
+	}
+
+
+	// Send an event to the observers, if any. It is dropped if they aren't ready for it.
+	//
+	pub(crate) fn notify( &self, event: IoEvent )
+	{
+		let Some( pharos ) = &self.pharos else { return };
+
+		let mut pharos = pharos.lock().unwrap_or_else( PoisonError::into_inner );
+
+		let     waker   = noop_waker();
+		let mut context = Context::from_waker( &waker );
+
+		if let Poll::Ready( Ok(()) ) = Pin::new( &mut *pharos ).poll_ready( &mut context )
+		{
+			let _ = Pin::new( &mut *pharos ).start_send( event );
+		}
+	}
+}
@@ -0,0 +1,149 @@
+use
+{
+	crate   :: { IoStream                          } ,
+	std     :: { io, fmt                           } ,
+	std     :: { pin::Pin, task::{ Poll, Context } } ,
+	futures :: { TryStream, Stream, Sink, ready    } ,
+};
+
+
+
+impl<St, I> IoStream<St, I>
+where
+
+	St: Unpin,
+
+{
+	/// Read and send whole items of the wrapped transport, while keeping the state of the
+	/// IoStream. Items and bytes can be mixed on the same connection this way, which is not the
+	/// case when going through [`IoStream::inner_mut`]: partially read items, the write buffer and
+	/// deferred errors are taken into account.
+	///
+	/// This is a separate type so the [`Stream`] and [`Sink`] methods don't clash with the ones
+	/// of [`AsyncRead`](futures::AsyncRead) and [`AsyncWrite`](futures::AsyncWrite), like `flush`
+	/// and `close`.
+	//
+	pub fn items( &mut self ) -> Items<'_, St, I>
+	{
+		Items{ io: self }
+	}
+}
+
+
+
+/// Access to the items of an [`IoStream`], obtained from [`IoStream::items`].
+//
+pub struct Items<'a, St, I>
+where
+
+	St: Unpin,
+
+{
+	io: &'a mut IoStream<St, I>,
+}
+
+
+
+impl<St, I> fmt::Debug for Items<'_, St, I>
+where
+
+	St: Unpin,
+
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		f.debug_struct( "Items" ).field( "io", &self.io ).finish()
+	}
+}
+
+
+
+/// Items can be read directly, which is the same as [`IoStream::poll_next_item`]. The rest of an item
+/// that was partially read through [`AsyncRead`](futures::AsyncRead) or
+/// [`AsyncBufRead`](futures::AsyncBufRead) is returned first.
+//
+impl<St, I> Stream for Items<'_, St, I>
+where
+
+	St: TryStream< Ok=I, Error=io::Error > + Unpin,
+	I: AsRef<[u8]> + From< Vec<u8> >,
+
+{
+	type Item = io::Result<I>;
+
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Option<Self::Item> >
+	{
+		Pin::new( &mut *self.io ).poll_next_item( cx )
+	}
+}
+
+
+
+/// Items can be sent directly. A deferred error from an earlier write is returned first, and data
+/// in the write buffer is sent before new items, so items and bytes are never reordered.
+///
+/// Items sent this way are never split or checked against [`IoStream::with_max_item_size`], and
+/// they are not flushed implicitly.
+//
+impl<St, I> Sink<I> for Items<'_, St, I>
+where
+
+	St: Sink< I, Error=io::Error > + Unpin,
+	I: AsRef<[u8]> + From< Vec<u8> >,
+
+{
+	type Error = io::Error;
+
+	fn poll_ready( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		let io = &mut *self.io;
+
+		io.poll_pending_flush( cx );
+
+		if let Some( e ) = io.write_err.take()
+		{
+			return Poll::Ready( Err(e) );
+		}
+
+		while io.write_buf.len() > io.write_tail()
+		{
+			ready!( Pin::new( &mut *io ).poll_send_buffer( cx ) )?;
+		}
+
+		io.poll_inner_ready( cx )
+	}
+
+
+	fn start_send( mut self: Pin<&mut Self>, item: I ) -> io::Result<()>
+	{
+		let len = item.as_ref().len();
+
+		Pin::new( &mut self.io.inner ).start_send( item )?;
+
+		self.io.sent( len );
+
+		Ok(())
+	}
+
+
+	fn poll_flush( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		if let Some( e ) = self.io.write_err.take()
+		{
+			return Poll::Ready( Err(e) );
+		}
+
+		Pin::new( &mut *self.io ).poll_flush_impl( cx )
+	}
+
+
+	fn poll_close( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		if let Some( e ) = self.io.write_err.take()
+		{
+			return Poll::Ready( Err(e) );
+		}
+
+		Pin::new( &mut *self.io ).poll_close_impl( cx )
+	}
+}
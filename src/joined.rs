@@ -0,0 +1,178 @@
+use
+{
+	crate   :: { IoStream, TextStream              } ,
+	std     :: { io                                } ,
+	std     :: { pin::Pin, task::{ Poll, Context } } ,
+	futures :: { Stream, Sink                      } ,
+};
+
+
+
+impl<Rx, Tx, I> IoStream< Joined<Rx, Tx>, I >
+where
+
+	Rx: Stream< Item=io::Result<I> > + Unpin,
+	Tx: Sink< I, Error=io::Error >   + Unpin,
+
+{
+	/// Create an IoStream from a separate stream and sink, for transports that come as two objects,
+	/// like the two ends of a channel or the halves of an already split connection. Reads come from
+	/// `stream` and writes go to `sink`, with the same semantics as [`IoStream::new`].
+	///
+	/// ```
+	/// use
+	/// {
+	///    async_io_stream :: { IoStream                                     } ,
+	///    futures         :: { channel::mpsc, StreamExt, SinkExt, AsyncRead } ,
+	///    futures         :: { AsyncWrite                                   } ,
+	///    std             :: { io                                           } ,
+	/// };
+	///
+	/// let (tx, rx) = mpsc::channel::< Vec<u8> >( 8 );
+	///
+	/// let stream = rx.map( Ok );
+	/// let sink   = tx.sink_map_err( |_| io::Error::from( io::ErrorKind::NotConnected ) );
+	///
+	/// let io = IoStream::join( stream, sink );
+	///
+	/// fn is_io( _: &( impl AsyncRead + AsyncWrite ) ) {}
+	///
+	/// is_io( &io );
+	/// ```
+	//
+	pub fn join( stream: Rx, sink: Tx ) -> Self
+	{
+		Self::new( Joined{ stream, sink } )
+	}
+}
+
+
+
+impl<Rx, Tx, I> TextStream< Joined<Rx, Tx>, I >
+where
+
+	Rx: Stream< Item=io::Result<I> > + Unpin,
+	Tx: Sink< I, Error=io::Error >   + Unpin,
+
+{
+	/// Create a TextStream from a separate stream and sink, like [`IoStream::join`].
+	//
+	pub fn join( stream: Rx, sink: Tx ) -> Self
+	{
+		Self::new( Joined{ stream, sink } )
+	}
+}
+
+
+
+/// A stream and a sink combined into one object that implements both [`Stream`] and [`Sink`].
+/// Created by [`IoStream::join`].
+//
+#[ derive( Debug ) ]
+//
+pub struct Joined<Rx, Tx>
+{
+	stream: Rx,
+	sink  : Tx,
+}
+
+
+impl<Rx, Tx> Joined<Rx, Tx>
+{
+	/// Get a reference to the stream.
+	//
+	pub fn stream( &self ) -> &Rx
+	{
+		&self.stream
+	}
+
+
+	/// Get a mut reference to the stream.
+	//
+	pub fn stream_mut( &mut self ) -> &mut Rx
+	{
+		&mut self.stream
+	}
+
+
+	/// Get a reference to the sink.
+	//
+	pub fn sink( &self ) -> &Tx
+	{
+		&self.sink
+	}
+
+
+	/// Get a mut reference to the sink.
+	//
+	pub fn sink_mut( &mut self ) -> &mut Tx
+	{
+		&mut self.sink
+	}
+
+
+	/// Separate the stream and the sink again.
+	//
+	pub fn into_inner( self ) -> (Rx, Tx)
+	{
+		( self.stream, self.sink )
+	}
+}
+
+
+
+impl<Rx, Tx> Stream for Joined<Rx, Tx>
+where
+
+	Rx: Stream + Unpin,
+	Tx: Unpin,
+
+{
+	type Item = Rx::Item;
+
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Option<Self::Item> >
+	{
+		Pin::new( &mut self.stream ).poll_next( cx )
+	}
+
+
+	fn size_hint( &self ) -> (usize, Option<usize>)
+	{
+		self.stream.size_hint()
+	}
+}
+
+
+
+impl<Rx, Tx, I> Sink<I> for Joined<Rx, Tx>
+where
+
+	Rx: Unpin,
+	Tx: Sink<I> + Unpin,
+
+{
+	type Error = Tx::Error;
+
+	fn poll_ready( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Result<(), Self::Error> >
+	{
+		Pin::new( &mut self.sink ).poll_ready( cx )
+	}
+
+
+	fn start_send( mut self: Pin<&mut Self>, item: I ) -> Result<(), Self::Error>
+	{
+		Pin::new( &mut self.sink ).start_send( item )
+	}
+
+
+	fn poll_flush( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Result<(), Self::Error> >
+	{
+		Pin::new( &mut self.sink ).poll_flush( cx )
+	}
+
+
+	fn poll_close( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Result<(), Self::Error> >
+	{
+		Pin::new( &mut self.sink ).poll_close( cx )
+	}
+}
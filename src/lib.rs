@@ -27,15 +27,43 @@
 //
 use
 {
-	std          :: { fmt, io::{ self, Read, Cursor, IoSlice, IoSliceMut, BufRead }                 } ,
-	std          :: { pin::Pin, task::{ Poll, Context }, borrow::{ Borrow, BorrowMut }              } ,
+	std          :: { fmt, any, io::{ self, Read, Cursor, IoSlice, IoSliceMut, BufRead }            } ,
+	std          :: { collections::VecDeque                                                      } ,
+	std          :: { pin::Pin, task::{ Poll, Context }, borrow::{ Borrow, BorrowMut }, mem         } ,
 	futures      :: { TryStream, ready, Sink, task::noop_waker, AsyncRead, AsyncWrite, AsyncBufRead } ,
+	futures      :: { future::poll_fn                                                                } ,
 };
 
 
+mod duplex;
+mod items ;
+mod joined;
+mod split ;
+mod state ;
+mod stats ;
+mod text  ;
+
+#[ cfg( feature = "map_pharos" ) ] mod events;
+#[ cfg( feature = "map_pharos" ) ] pub use events::IoEvent;
+#[ cfg( feature = "map_pharos" ) ] use events::SharedPharos;
+
+#[ cfg( feature = "bytes" ) ] mod arena;
+#[ cfg( feature = "bytes" ) ] use arena::Arena;
+
+pub use duplex::{ Duplex                                 };
+pub use items ::{ Items                                  };
+pub use joined::{ Joined                                 };
+pub use split ::{ IoReadHalf, IoWriteHalf, ReuniteError };
+pub use state ::{ IoState                               };
+pub use stats ::{ Stats                                 };
+pub use text  ::{ TextStream                            };
+
+use text::TextFn;
+
+
 #[ cfg( feature = "tokio_io" ) ]
 //
-use tokio::io::{ AsyncRead as TokAsyncRead, AsyncWrite as TokAsyncWrite };
+use tokio::io::{ AsyncRead as TokAsyncRead, AsyncWrite as TokAsyncWrite, AsyncBufRead as TokAsyncBufRead };
 
 #[ cfg( feature = "map_pharos" ) ]
 //
@@ -46,31 +74,258 @@ use
 };
 
 
-// A buffer for the current message or error.
+/// A buffered message, error or the end of the stream. The read side state of an [`IoStream`] is a
+/// queue of these, as exposed through [`IoParts`].
+//
+#[ derive(Debug) ]
+//
+pub enum ReadState<B>
+{
+	/// An item that was partially read. The position of the cursor marks how many bytes have
+	/// already been returned to the reader.
+	//
+	Ready{ /** The partially read item. */ chunk: Cursor<B> } ,
+
+	/// An error from the stream that will be returned from the next read.
+	//
+	Error{ /** The buffered error. */ error: io::Error } ,
+
+	/// The stream has ended.
+	//
+	Eof,
+}
+
+
+
+/// The inner transport of an [`IoStream`] together with all the state that was buffered inside
+/// the [`IoStream`]. Obtained from [`IoStream::into_parts`] and turned back into an [`IoStream`]
+/// with [`IoStream::from_parts`].
+///
+/// This allows taking the transport back out of an [`IoStream`] without losing data, for example
+/// to go back to handling it as a stream of messages after having parsed a handshake through
+/// [`AsyncBufRead`].
 //
 #[ derive(Debug) ]
 //
-enum ReadState<B>
+pub struct IoParts<St, I>
+{
+	/// The wrapped transport.
+	//
+	pub inner: St,
+
+	/// The read side state, in the order it will be returned to the reader. For each
+	/// [`ReadState::Ready`], the bytes of the chunk after the cursor position have not yet been
+	/// returned. This usually holds one entry at most, but more items can be buffered by
+	/// [`IoStream::poll_peek`].
+	//
+	pub read_state: VecDeque< ReadState<I> >,
+
+	/// An error from flushing the sink that hasn't been returned from a write yet.
+	//
+	pub write_err: Option< io::Error >,
+
+	/// Data that was written but not yet sent to the sink because of
+	/// [`IoStream::with_write_buffer`].
+	//
+	pub write_buf: Vec<u8>,
+}
+
+
+impl<St, I> IoParts<St, I>
+{
+	/// Create parts for a transport without any buffered state.
+	//
+	pub fn new( inner: St ) -> Self
+	{
+		Self
+		{
+			inner                  ,
+			read_state: VecDeque::new() ,
+			write_err : None            ,
+			write_buf : Vec::new()      ,
+		}
+	}
+}
+
+
+// Settings of an IoStream. These are not part of IoParts, but they are carried over to the
+// halves by split.
+//
+#[ derive( Debug, Clone, Copy ) ]
+//
+struct Config
+{
+	// The size of the write buffer, 0 means writes are not buffered.
+	//
+	write_buffer: usize,
+
+	// The maximum number of bytes in an item we send to the sink.
+	//
+	max_item_size: usize,
+
+	// The maximum number of bytes in an item we accept from the stream.
+	//
+	max_read_item_size: usize,
+
+	// When to flush the sink after sending an item.
+	//
+	flush_policy: FlushPolicy,
+}
+
+
+impl Default for Config
+{
+	fn default() -> Self
+	{
+		Self
+		{
+			write_buffer      : 0                   ,
+			max_item_size     : usize::MAX          ,
+			max_read_item_size: usize::MAX          ,
+			flush_policy      : FlushPolicy::Always ,
+		}
+	}
+}
+
+
+
+/// When an [`IoStream`] flushes the sink after a write handed it an item. Set with
+/// [`IoStream::with_flush_policy`].
+//
+#[ derive( Debug, Clone, Copy, PartialEq, Eq, Default ) ]
+//
+pub enum FlushPolicy
+{
+	/// Flush after every item. This is the default, so data doesn't get stuck in the sink when the
+	/// writer never calls flush.
+	//
+	#[ default ]
+	//
+	Always,
+
+	/// Never flush implicitly, only when `poll_flush` or `poll_close` are called. Use this when the
+	/// sink does its own batching.
+	//
+	Never,
+
+	/// Flush once this many items have been sent since the last flush.
+	//
+	Items( usize ),
+
+	/// Flush once this many bytes have been sent since the last flush.
+	//
+	Bytes( usize ),
+}
+
+
+
+// Counts what was sent to the sink since the last successful flush.
+//
+#[ derive( Debug, Clone, Copy, Default ) ]
+//
+struct Unflushed
+{
+	items: usize,
+	bytes: usize,
+}
+
+
+
+// The poll_flush of the inner sink. An implicit flush that returned pending is remembered as this, so
+// it can be driven from methods that don't know St is a sink, like poll_read.
+//
+pub(crate) type FlushFn<St> = fn( Pin<&mut St>, &mut Context<'_> ) -> Poll< io::Result<()> >;
+
+
+
+// Creates an item from bytes that were merged from several items. It can only be set where
+// I: From<Vec<u8>>, but it is used in methods that don't require that, like poll_fill_buf.
+//
+pub(crate) type MergeFn<I> = fn( Vec<u8> ) -> I;
+
+
+
+// Something read data can be copied into.
+//
+pub(crate) trait ReadTarget
 {
-	Ready{ chunk: Cursor<B> } ,
-	Error{ error: io::Error } ,
-	Eof                       ,
+	// How many more bytes fit.
+	//
+	fn remaining( &self ) -> usize;
+
+	// Append data. Never called with more than `remaining` bytes.
+	//
+	fn put_slice( &mut self, data: &[u8] );
+}
+
+
+// Like `Write for &mut [u8]`, the slice is advanced past the data that was written.
+//
+impl ReadTarget for &mut [u8]
+{
+	fn remaining( &self ) -> usize
+	{
+		self.len()
+	}
+
+
+	fn put_slice( &mut self, data: &[u8] )
+	{
+		let (head, tail) = mem::take( self ).split_at_mut( data.len() );
+
+		head.copy_from_slice( data );
+		*self = tail;
+	}
+}
+
+
+#[ cfg( feature = "tokio_io" ) ]
+//
+impl ReadTarget for tokio::io::ReadBuf<'_>
+{
+	fn remaining( &self ) -> usize
+	{
+		tokio::io::ReadBuf::remaining( self )
+	}
+
+
+	fn put_slice( &mut self, data: &[u8] )
+	{
+		tokio::io::ReadBuf::put_slice( self, data )
+	}
 }
 
 
+
 /// A wrapper over a TryStream + Sink that implements [`AsyncRead`]/[`AsyncWrite`] and [`AsyncBufRead`].
 /// See the readme for more information.
 ///
-/// Implements [`tokio::io::AsyncRead`] and [`tokio::io::AsyncWrite`] when the `tokio_io` feature is enabled.
+/// Implements [`tokio::io::AsyncRead`], [`tokio::io::AsyncBufRead`] and [`tokio::io::AsyncWrite`] when the `tokio_io`
+/// feature is enabled.
 //
 pub struct IoStream<St, I>
 where
 
 	St: Unpin,
 {
-	inner    : St                   ,
-	state    : Option<ReadState<I>> ,
-	write_err: Option<io::Error>    ,
+	inner        : St                              ,
+	state        : VecDeque< ReadState<I> >        ,
+	write_err    : Option< io::Error >             ,
+	write_buf    : Vec<u8>                         ,
+	unflushed    : Unflushed                       ,
+	pending_flush: Option< FlushFn<St> >           ,
+	text         : Option< TextFn<I>   >           ,
+	min_fill     : Option< (usize, MergeFn<I>) >   ,
+	stats        : Stats                           ,
+	config       : Config                          ,
+
+	#[ cfg( feature = "bytes" ) ]
+	//
+	arena: Option< Arena<I> >,
+
+	#[ cfg( feature = "map_pharos" ) ]
+	//
+	pharos: SharedPharos,
 }
 
 impl<St, I> Unpin for IoStream<St, I>
@@ -92,13 +347,108 @@ where
 	{
 		Self
 		{
-			inner            ,
-			state     : None ,
-			write_err : None ,
+			inner                            ,
+			state        : VecDeque::new()      ,
+			write_err    : None                 ,
+			write_buf    : Vec::new()           ,
+			unflushed    : Unflushed::default() ,
+			pending_flush: None                 ,
+			text         : None                 ,
+			min_fill     : None                 ,
+			stats        : Stats::default()     ,
+			config       : Config::default()    ,
+
+			#[ cfg( feature = "bytes" ) ]
+			//
+			arena: None,
+
+			#[ cfg( feature = "map_pharos" ) ]
+			//
+			pharos: None,
 		}
 	}
 
 
+	/// Buffer writes until `capacity` bytes have accumulated, and only then send them as one item.
+	/// This avoids flooding the sink with tiny items when the writer uses small buffers, like
+	/// codecs or `write!` do. Buffered data is also sent on `poll_flush` and `poll_close`.
+	///
+	/// When the buffer is full, `poll_write` will return `Pending` until the sink accepts it. A
+	/// write that would fill an empty buffer by itself is sent directly as one item.
+	///
+	/// A capacity of 0, the default, disables buffering so every write creates one item.
+	//
+	pub fn with_write_buffer( mut self, capacity: usize ) -> Self
+	{
+		self.config.write_buffer = capacity;
+		self
+	}
+
+
+	/// Choose when the sink is flushed after a write handed it an item. The default is
+	/// [`FlushPolicy::Always`].
+	//
+	pub fn with_flush_policy( mut self, policy: FlushPolicy ) -> Self
+	{
+		self.config.flush_policy = policy;
+		self
+	}
+
+
+	/// Never send items bigger than `max` bytes to the sink, for transports that have a maximum
+	/// message size. Writes bigger than this will only accept `max` bytes, returning a short write
+	/// as [`AsyncWrite`] allows. Callers that use `write_all` will just see their data split over
+	/// several items. The write buffer size is capped to `max` as well.
+	///
+	/// # Panics
+	///
+	/// When `max` is 0, or smaller than 4 for a [`TextStream`].
+	//
+	pub fn with_max_item_size( mut self, max: usize ) -> Self
+	{
+		assert!( max > 0, "IoStream: the maximum item size must be at least 1" );
+		assert!( max >= 4 || self.text.is_none(), "IoStream: text mode needs a maximum item size of at least 4" );
+
+		self.config.max_item_size = max;
+		self
+	}
+
+
+	/// Refuse items from the stream that are bigger than `max` bytes. Such an item is dropped and an
+	/// error of kind [`io::ErrorKind::InvalidData`] is returned from the read in its place. Reading
+	/// can continue afterwards with the next item.
+	///
+	/// This prevents a peer from making us hold on to arbitrarily big items. Note that the inner
+	/// stream has already received the item, so transports that can limit message sizes themselves
+	/// should be configured to do so as well.
+	//
+	pub fn with_max_read_item_size( mut self, max: usize ) -> Self
+	{
+		self.config.max_read_item_size = max;
+		self
+	}
+
+
+	/// Make `poll_fill_buf` return at least `min` bytes when they are available, so a peer sending
+	/// many tiny items doesn't make `read_line` and the like loop once per item. Items that are
+	/// ready are merged until the buffer holds `min` bytes, or the stream is pending, ends or returns
+	/// an error. An error or the end of the stream is returned after the merged bytes.
+	///
+	/// Merging copies the items into a new one, which [`IoStream::poll_next_item`] returns as one
+	/// item. Items that hold `min` bytes by themselves are returned as is. A `min` of 0, the default,
+	/// returns one item at a time.
+	//
+	pub fn with_min_fill_buf( mut self, min: usize ) -> Self
+	where
+
+		I: From< Vec<u8> >,
+
+	{
+		self.min_fill = if min == 0 { None } else { Some(( min, I::from )) };
+		self
+	}
+
+
 	/// Get a reference to the inner stream.
 	//
 	pub fn inner( &self ) -> &St
@@ -115,93 +465,631 @@ where
 	}
 
 
-
-	// The requirements:
-	// - fill as much of the passed in buffer as we can.
-	// - the item coming out of the stream might be bigger than the read buffer, so then we need
-	//   to buffer it internally and keep track of how many bytes are left for next time.
-	// - the item might be smaller than the buffer, but then we need to get the next item
-	//   out of the stream. So the stream might return:
-	//   - the next item,  hurray
-	//   - pending
-	//   - an error.
-	//
-	//   If it returns pending, we cannot return pending as we already have copied bytes into the
-	//   output buffer, so we need to return Poll::Ready( Ok(n) ) where n is the number of bytes read.
+	/// Take the inner stream back out together with any state buffered in the IoStream: the unread
+	/// tail of the current item, a stream error that has not been returned yet, an error
+	/// from flushing the sink that will be returned from the next write and data in the write buffer.
+	///
+	/// Use [`IoStream::from_parts`] to restore the IoStream. Settings like the write buffer size
+	/// are not part of the parts.
 	//
-	//   However, the stream will wake up the waker that it got, but we didn't return pending here,
-	//   so to avoid the spurious wakeups, we will just call that with a dummy waker if we already
-	//   have data to return.
+	pub fn into_parts( self ) -> IoParts<St, I>
+	{
+		IoParts
+		{
+			inner     : self.inner     ,
+			read_state: self.state     ,
+			write_err : self.write_err ,
+			write_buf : self.write_buf ,
+		}
+	}
+
+
+	/// Create an IoStream from parts previously obtained from [`IoStream::into_parts`]. Buffered
+	/// data and errors will be returned before anything else is taken from the inner stream.
+	///
+	/// Items in [`ReadState::Ready`] which have no bytes left after the cursor position are
+	/// discarded. Data in the write buffer will be sent before any new data, even if no write
+	/// buffer is configured on the new IoStream.
 	//
-	//   If it returns an error, we now need to buffer that error for the next call to poll_read,
-	//   because again we can not return it immediately.
+	pub fn from_parts( parts: IoParts<St, I> ) -> Self
+	where
+
+		I: AsRef<[u8]>,
+
+	{
+		let mut state = parts.read_state;
+
+		state.retain( |s| !matches!( s, ReadState::Ready{ chunk } if chunk.position() >= chunk.get_ref().as_ref().len() as u64 ) );
+
+		Self
+		{
+			inner        : parts.inner          ,
+			state                               ,
+			write_err    : parts.write_err      ,
+			write_buf    : parts.write_buf      ,
+			unflushed    : Unflushed::default() ,
+			pending_flush: None                 ,
+			text         : None                 ,
+			min_fill     : None                 ,
+			stats        : Stats::default()     ,
+			config       : Config::default()    ,
+
+			#[ cfg( feature = "bytes" ) ]
+			//
+			arena: None,
+
+			#[ cfg( feature = "map_pharos" ) ]
+			//
+			pharos: None,
+		}
+	}
+
+
+
+	// Get the next item from the stream. Items bigger than the maximum read item size are turned
+	// into an error.
 	//
-	fn poll_read_impl( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8] ) -> Poll< io::Result<usize> >
+	fn poll_stream( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Option<io::Result<I>> >
 	where
 
 		St: TryStream< Ok=I, Error=io::Error >,
 		I: AsRef<[u8]>,
 
 	{
-		// since we might call the inner stream several times, keep track of whether we have data to
-		// return. If we do, we cannot return pending or error, but need to buffer the error for next
-		// call.
-		//
-		let mut have_read = 0;
-		let mut state     = self.state.take();
+		let item = ready!( Pin::new( &mut self.inner ).try_poll_next( cx ) );
 
-		loop { match state
+		if let Some( Ok(chunk) ) = &item
 		{
-			Some( ReadState::Eof ) => return Poll::Ready( Ok(0) ),
+			let max = self.config.max_read_item_size;
+			let len = chunk.as_ref().len();
 
-			// A buffered error from the last call to poll_read.
-			//
-			Some( ReadState::Error{ error } ) =>
+			if len > max
 			{
-				self.state = None;
-				return Poll::Ready( Err(error) )
+				let msg = format!( "IoStream: received an item of {len} bytes, the maximum is {max}" );
+
+				return Poll::Ready(Some( Err( io::Error::new( io::ErrorKind::InvalidData, msg ) ) ));
 			}
 
-			Some( ReadState::Ready{ ref mut chunk } ) =>
-			{
-				have_read += chunk.read( &mut buf[have_read..] ).expect( "no io errors on cursor" );
+			self.stats.read( len );
 
-				// We read the entire chunk
-				//
-				if chunk.position() == chunk.get_ref().as_ref().len() as u64
-				{
-					state = None;
-				}
+			#[ cfg( feature = "map_pharos" ) ]
+			//
+			self.notify( IoEvent::ItemReceived{ len } );
+		}
 
+		#[ cfg( feature = "map_pharos" ) ]
+		//
+		if item.is_none()
+		{
+			self.notify( IoEvent::Eof );
+		}
 
-				// The buffer is full, we are done.
-				//
-				if have_read == buf.len()
+		Poll::Ready( item )
+	}
+
+
+	/// Get the next item out of the stream without copying it into a read buffer. If an item was
+	/// partially read through [`AsyncRead`] or [`AsyncBufRead`], the unread rest of it is returned
+	/// first, so reading bytes and items can be interleaved on the same stream without losing data.
+	/// A buffered error is returned before polling the stream again.
+	///
+	/// Returns `None` when the stream has ended.
+	///
+	/// The rest of a partially read item is copied into a new item. With the `bytes` feature and
+	/// `I = Bytes`, it is a zero copy slice of the original item instead, but only once
+	/// [`IoStream::with_arena`] has been called. `IoStream::new` works for any `I`, so it can't
+	/// tell that the items are `Bytes`. `with_arena` is where that is known, so it sets up the
+	/// slicing for reads along with the arena for writes. Without it, an `IoStream<St, Bytes>`
+	/// copies the rest of the item like for any other `I`. Since the arena only grows when
+	/// writing, `with_arena( 0 )` enables slicing for a stream that is only read from.
+	//
+	pub fn poll_next_item( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Option<io::Result<I>> >
+	where
+
+		St: TryStream< Ok=I, Error=io::Error >,
+		I: AsRef<[u8]> + From< Vec<u8> >,
+
+	{
+		self.poll_pending_flush( cx );
+
+		match self.state.pop_front()
+		{
+			Some( ReadState::Ready{ chunk } ) => Poll::Ready(Some( Ok( self.unread_tail( chunk ) ) )),
+			Some( ReadState::Error{ error } ) => Poll::Ready(Some( Err( error )                   )),
+
+			Some( ReadState::Eof ) =>
+			{
+				self.state.push_front( ReadState::Eof );
+				Poll::Ready( None )
+			}
+
+			None => match ready!( self.as_mut().poll_stream( cx ) )
+			{
+				None =>
 				{
-					self.state = state;
-					return Poll::Ready( Ok(have_read) );
+					self.state.push_back( ReadState::Eof );
+					Poll::Ready( None )
 				}
+
+				item => Poll::Ready( item ),
 			}
+		}
+	}
 
 
-			None =>
-			{
-				if have_read == 0
-				{
-					match ready!( Pin::new( &mut self.inner ).try_poll_next( cx ) )
-					{
-						// We have an item. Store it and continue the loop.
-						//
-						Some(Ok( chunk )) =>
-						{
-							state = ReadState::Ready { chunk: Cursor::new(chunk) }.into();
-						}
+	/// Get the next item out of the stream. See [`IoStream::poll_next_item`].
+	//
+	pub async fn next_item( &mut self ) -> Option< io::Result<I> >
+	where
 
-						// The stream has ended
+		St: TryStream< Ok=I, Error=io::Error >,
+		I: AsRef<[u8]> + From< Vec<u8> >,
+
+	{
+		poll_fn( |cx| Pin::new( &mut *self ).poll_next_item( cx ) ).await
+	}
+
+
+	/// Read bytes from the current item only, never from the next one, so message boundaries are
+	/// preserved. Returns how many bytes were read and whether they ended the item. Reading an item
+	/// bigger than `buf` takes several calls, the last of which reports the end of the item.
+	///
+	/// `Ok((0, true))` means an empty item was read, `Ok((0, false))` means the stream has ended
+	/// (or `buf` is empty). This can be freely mixed with the other read methods.
+	//
+	pub fn poll_read_message( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8] ) -> Poll< io::Result<(usize, bool)> >
+	where
+
+		St: TryStream< Ok=I, Error=io::Error >,
+		I: AsRef<[u8]>,
+
+	{
+		self.poll_pending_flush( cx );
+
+		loop { match self.state.pop_front()
+		{
+			Some( ReadState::Eof ) =>
+			{
+				self.state.push_front( ReadState::Eof );
+				return Poll::Ready( Ok(( 0, false )) );
+			}
+
+			Some( ReadState::Error{ error } ) => return Poll::Ready( Err(error) ),
+
+			Some( ReadState::Ready{ mut chunk } ) =>
+			{
+				let read = chunk.read( buf ).expect( "no io errors on cursor" );
+				let end  = chunk.position() == chunk.get_ref().as_ref().len() as u64;
+
+				if !end
+				{
+					self.state.push_front( ReadState::Ready{ chunk } );
+				}
+
+				return Poll::Ready( Ok(( read, end )) );
+			}
+
+			None => match ready!( self.as_mut().poll_stream( cx ) )
+			{
+				Some( Ok(chunk) ) => self.state.push_back( ReadState::Ready{ chunk: Cursor::new(chunk) } ),
+				Some( Err(e)    ) => return Poll::Ready( Err(e) ),
+				None              => self.state.push_back( ReadState::Eof ),
+			}
+		}}
+	}
+
+
+	/// Read bytes from the current item only. See [`IoStream::poll_read_message`].
+	//
+	pub async fn read_message( &mut self, buf: &mut [u8] ) -> io::Result<(usize, bool)>
+	where
+
+		St: TryStream< Ok=I, Error=io::Error >,
+		I: AsRef<[u8]>,
+
+	{
+		poll_fn( |cx| Pin::new( &mut *self ).poll_read_message( cx, buf ) ).await
+	}
+
+
+	/// Look at the next `n` bytes without consuming them, for example to detect the protocol a peer
+	/// speaks. Items are taken from the stream until at least `n` bytes are buffered, and these are
+	/// returned as one slice, which can be longer than `n`. Later reads return the same bytes.
+	///
+	/// Fewer than `n` bytes are returned when the stream ends, or returns an error after some bytes.
+	/// The error is then returned by a read after the bytes. An error with nothing buffered is
+	/// returned right away. With `n` 0, what is buffered is returned without polling the stream.
+	///
+	/// When the bytes come from several items, they are copied into one, which is how
+	/// [`IoStream::poll_next_item`] and [`IoStream::poll_read_message`] will return them.
+	//
+	pub fn poll_peek( mut self: Pin<&mut Self>, cx: &mut Context<'_>, n: usize ) -> Poll< io::Result<&[u8]> >
+	where
+
+		St: TryStream< Ok=I, Error=io::Error >,
+		I: AsRef<[u8]> + From< Vec<u8> >,
+
+	{
+		self.poll_pending_flush( cx );
+
+		// An error that a previous read stopped in front of is returned now, like the next read would.
+		// Otherwise we would return an empty slice, which looks like the end of the stream.
+		//
+		self.skip_read_chunks();
+
+		match self.state.pop_front()
+		{
+			Some( ReadState::Error{ error } ) => return Poll::Ready( Err(error) ),
+			Some( other                     ) => self.state.push_front( other ) ,
+			None                              => {}
+		}
+
+		// Only bytes can be peeked, stop at an error or the end of the stream.
+		//
+		while self.ready_len() < n && self.state.iter().all( |s| matches!( s, ReadState::Ready{..} ) )
+		{
+			match ready!( self.as_mut().poll_stream( cx ) )
+			{
+				// An empty item would look like the end of the stream to poll_fill_buf.
+				//
+				Some( Ok(chunk) ) if chunk.as_ref().is_empty() => {}
+				Some( Ok(chunk) )                              => self.state.push_back( ReadState::Ready{ chunk: Cursor::new(chunk) } ),
+
+				Some( Err(error) ) if self.state.is_empty()    => return Poll::Ready( Err(error) ),
+				Some( Err(error) )                             => self.buffer_read_error( error ),
+
+				None                                           => self.state.push_back( ReadState::Eof ),
+			}
+		}
+
+		self.merge_ready( n, I::from );
+
+		match self.get_mut().state.front_mut()
+		{
+			Some( ReadState::Ready{ chunk } ) => Poll::Ready( chunk.fill_buf() ),
+			_                                 => Poll::Ready( Ok(&[]) ),
+		}
+	}
+
+
+	/// Put bytes back in front of the read buffer, so the next read returns them first, before any
+	/// data, error or end of the stream that is already buffered. Useful when a parser consumed
+	/// more than it should have. The bytes don't have to be ones that were read before.
+	///
+	/// The bytes are returned as a separate item by [`IoStream::poll_next_item`] and
+	/// [`IoStream::poll_read_message`]. Unreading nothing does nothing.
+	//
+	pub fn unread( &mut self, bytes: &[u8] )
+	where
+
+		I: From< Vec<u8> >,
+
+	{
+		if bytes.is_empty() { return }
+
+		self.state.push_front( ReadState::Ready{ chunk: Cursor::new( bytes.to_vec().into() ) } );
+	}
+
+
+	/// Get the bytes of every item that is buffered or ready in the stream without copying them, for
+	/// example to pass them all to a vectored write at once. Like [`AsyncBufRead::poll_fill_buf`],
+	/// this only returns `Pending` when nothing is buffered.
+	///
+	/// Up to `bufs.len()` items are put in `bufs`, the first one being what `poll_fill_buf` would
+	/// return. Returns how many were put, 0 meaning the end of the stream or that `bufs` is empty.
+	/// An error or the end of the stream after the returned bytes is returned by the next call.
+	///
+	/// Use [`IoStream::consume_bufs`] to mark bytes from several items as read.
+	//
+	pub fn poll_fill_bufs<'a>( mut self: Pin<&'a mut Self>, cx: &mut Context<'_>, bufs: &mut [ IoSlice<'a> ] ) -> Poll< io::Result<usize> >
+	where
+
+		St: TryStream< Ok=I, Error=io::Error >,
+		I: AsRef<[u8]>,
+
+	{
+		if bufs.is_empty() { return Poll::Ready( Ok(0) ) }
+
+		if ready!( self.as_mut().poll_fill_buf_impl( cx ) )?.is_empty()
+		{
+			return Poll::Ready( Ok(0) );
+		}
+
+		self.as_mut().take_ready_items( |this| this.ready_chunks() < bufs.len() );
+
+		let this: &'a Self = self.get_mut();
+		let mut filled     = 0;
+
+		for (s, buf) in this.state.iter().zip( bufs.iter_mut() )
+		{
+			let ReadState::Ready{ chunk } = s else { break };
+
+			*buf    = IoSlice::new( &chunk.get_ref().as_ref()[ chunk.position() as usize.. ] );
+			filled += 1;
+		}
+
+		Poll::Ready( Ok(filled) )
+	}
+
+
+	/// Mark `amount` bytes as read, which can span several of the items returned by
+	/// [`IoStream::poll_fill_bufs`]. Consuming more than is buffered consumes everything up to an
+	/// error or the end of the stream.
+	///
+	/// Returns how many items with unread bytes are still buffered.
+	//
+	pub fn consume_bufs( mut self: Pin<&mut Self>, mut amount: usize ) -> usize
+	where
+
+		I: AsRef<[u8]>,
+
+	{
+		while amount > 0
+		{
+			let Some( ReadState::Ready{ chunk } ) = self.state.front_mut() else { break };
+
+			let pos  = chunk.position() as usize;
+			let left = chunk.get_ref().as_ref().len() - pos;
+
+			if amount < left
+			{
+				chunk.set_position( (pos + amount) as u64 );
+				break;
+			}
+
+			amount -= left;
+			self.state.pop_front();
+		}
+
+		self.ready_chunks()
+	}
+
+
+	// Take the items the stream has ready, without waiting for more, while `more` returns true.
+	// Stops at an error or the end of the stream.
+	//
+	fn take_ready_items( mut self: Pin<&mut Self>, more: impl Fn( &Self ) -> bool )
+	where
+
+		St: TryStream< Ok=I, Error=io::Error >,
+		I: AsRef<[u8]>,
+
+	{
+		// We already have data to return, so make sure the stream doesn't try to wake up the task.
+		//
+		let     waker   = noop_waker();
+		let mut context = Context::from_waker( &waker );
+
+		while more( &self ) && self.state.iter().all( |s| matches!( s, ReadState::Ready{..} ) )
+		{
+			match self.as_mut().poll_stream( &mut context )
+			{
+				Poll::Ready( Some(Ok( chunk )) ) =>
+				{
+					if !chunk.as_ref().is_empty()
+					{
+						self.state.push_back( ReadState::Ready{ chunk: Cursor::new( chunk ) } );
+					}
+				}
+
+				Poll::Ready( Some(Err( error )) ) => self.buffer_read_error( error )       ,
+				Poll::Ready( None               ) => self.state.push_back( ReadState::Eof ),
+				Poll::Pending                     => break                                 ,
+			}
+		}
+	}
+
+
+	// The number of chunks at the front of the queue, up to the first error or the end of the stream.
+	//
+	fn ready_chunks( &self ) -> usize
+	{
+		self.state.iter().take_while( |s| matches!( s, ReadState::Ready{..} ) ).count()
+	}
+
+
+	// The number of bytes in the chunks at the front of the queue, up to the first error or the end
+	// of the stream.
+	//
+	fn ready_len( &self ) -> usize
+	where
+
+		I: AsRef<[u8]>,
+
+	{
+		self.state.iter()
+
+			.map_while( |s| match s
+			{
+				ReadState::Ready{ chunk } => Some( chunk.get_ref().as_ref().len() - chunk.position() as usize ),
+				_                         => None,
+			})
+
+			.sum()
+	}
+
+
+	// Drop chunks at the front of the queue that were read entirely, as well as empty items.
+	//
+	fn skip_read_chunks( &mut self )
+	where
+
+		I: AsRef<[u8]>,
+
+	{
+		while let Some( ReadState::Ready{ chunk } ) = self.state.front()
+		{
+			if chunk.position() < chunk.get_ref().as_ref().len() as u64 { break }
+
+			self.state.pop_front();
+		}
+	}
+
+
+	// Make sure the first chunk in the queue holds at least `n` bytes, or all the bytes at the front
+	// of the queue if there are less, by copying the next chunks into one.
+	//
+	fn merge_ready( &mut self, n: usize, make: MergeFn<I> )
+	where
+
+		I: AsRef<[u8]>,
+
+	{
+		let mut merged = Vec::new();
+		let mut count  = 0;
+
+		for s in &self.state
+		{
+			let ReadState::Ready{ chunk } = s else { break };
+
+			if merged.len() >= n { break }
+
+			merged.extend_from_slice( &chunk.get_ref().as_ref()[ chunk.position() as usize.. ] );
+			count += 1;
+		}
+
+		if count < 2 { return }
+
+		self.state.drain( ..count );
+		self.state.push_front( ReadState::Ready{ chunk: Cursor::new( make( merged ) ) } );
+	}
+
+
+	// Turn what is left of a partially read item into an item.
+	//
+	fn unread_tail( &self, chunk: Cursor<I> ) -> I
+	where
+
+		I: AsRef<[u8]> + From< Vec<u8> >,
+
+	{
+		let pos = chunk.position() as usize;
+
+		if pos == 0
+		{
+			return chunk.into_inner();
+		}
+
+		#[ cfg( feature = "bytes" ) ]
+		//
+		if let Some( arena ) = &self.arena
+		{
+			return arena.slice( chunk.into_inner(), pos );
+		}
+
+		chunk.get_ref().as_ref()[pos..].to_vec().into()
+	}
+
+
+	// The requirements:
+	// - fill as much of the passed in buffer as we can.
+	// - the item coming out of the stream might be bigger than the read buffer, so then we need
+	//   to buffer it internally and keep track of how many bytes are left for next time.
+	// - the item might be smaller than the buffer, but then we need to get the next item
+	//   out of the stream. So the stream might return:
+	//   - the next item,  hurray
+	//   - pending
+	//   - an error.
+	//
+	//   If it returns pending, we cannot return pending as we already have copied bytes into the
+	//   output buffer, so we need to return Poll::Ready( Ok(n) ) where n is the number of bytes read.
+	//
+	//   However, the stream will wake up the waker that it got, but we didn't return pending here,
+	//   so to avoid the spurious wakeups, we will just call that with a dummy waker if we already
+	//   have data to return.
+	//
+	//   If it returns an error, we now need to buffer that error for the next call to poll_read,
+	//   because again we can not return it immediately.
+	//
+	// The data is copied into a ReadTarget, so the tokio impl can fill a ReadBuf without initializing
+	// it first.
+	//
+	fn poll_read_impl<T>( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut T ) -> Poll< io::Result<usize> >
+	where
+
+		T: ReadTarget + ?Sized,
+
+		St: TryStream< Ok=I, Error=io::Error >,
+		I: AsRef<[u8]>,
+
+	{
+		// since we might call the inner stream several times, keep track of whether we have data to
+		// return. If we do, we cannot return pending or error, but need to buffer the error for next
+		// call.
+		//
+		let mut have_read = 0;
+
+		// Entries in front of the queue are read first. Bytes can be queued before an error or the
+		// end of the stream by poll_peek.
+		//
+		loop { match self.state.pop_front()
+		{
+			Some( ReadState::Eof ) =>
+			{
+				self.state.push_front( ReadState::Eof );
+				return Poll::Ready( Ok(have_read) );
+			}
+
+			// A buffered error from the last call to poll_read. If we already have data, it has to
+			// wait for the next call.
+			//
+			Some( ReadState::Error{ error } ) =>
+			{
+				if have_read > 0
+				{
+					self.state.push_front( ReadState::Error{ error } );
+					return Poll::Ready( Ok(have_read) );
+				}
+
+				return Poll::Ready( Err(error) )
+			}
+
+			Some( ReadState::Ready{ mut chunk } ) =>
+			{
+				let pos  = chunk.position() as usize;
+				let data = &chunk.get_ref().as_ref()[pos..];
+				let n    = data.len().min( buf.remaining() );
+
+				buf.put_slice( &data[..n] );
+				chunk.set_position( (pos + n) as u64 );
+
+				have_read += n;
+
+				// Keep what is left of the chunk for next time.
+				//
+				if chunk.position() < chunk.get_ref().as_ref().len() as u64
+				{
+					self.state.push_front( ReadState::Ready{ chunk } );
+				}
+
+
+				// The buffer is full, we are done.
+				//
+				if buf.remaining() == 0
+				{
+					return Poll::Ready( Ok(have_read) );
+				}
+			}
+
+
+			None =>
+			{
+				if have_read == 0
+				{
+					match ready!( self.as_mut().poll_stream( cx ) )
+					{
+						// We have an item. Store it and continue the loop.
+						//
+						Some(Ok( chunk )) =>
+						{
+							self.state.push_back( ReadState::Ready { chunk: Cursor::new(chunk) } );
+						}
+
+						// The stream has ended
 						//
 						None =>
 						{
-							self.state = ReadState::Eof.into();
+							self.state.push_back( ReadState::Eof );
 							return Ok(0).into();
 						}
 
@@ -210,310 +1098,769 @@ where
 							// We didn't put anything in the passed in buffer, so just
 							// return the error.
 							//
-							self.state = None;
 							return Poll::Ready(Err( err ))
 						}
 					}
 				}
 
-				// there is already data ready to be returned, but the passed in buffer still
-				// has space, so we try to get another item out of the stream.
-				//
-				else
+				// there is already data ready to be returned, but the passed in buffer still
+				// has space, so we try to get another item out of the stream.
+				//
+				else
+				{
+					// We won't be able to return pending as we already have data, so make sure
+					// the stream doesn't try to wake up the task.
+					//
+					let     waker   = noop_waker();
+					let mut context = Context::from_waker( &waker );
+
+					match self.as_mut().poll_stream( &mut context )
+					{
+						// We have an item. Store it and continue the loop.
+						//
+						Poll::Ready( Some(Ok( chunk )) ) =>
+
+							self.state.push_back( ReadState::Ready { chunk: Cursor::new(chunk) } ),
+
+
+						// The stream has ended
+						//
+						Poll::Ready( None ) =>
+						{
+							// return whatever we had already read.
+							//
+							self.state.push_back( ReadState::Eof );
+							return Ok(have_read).into();
+						}
+
+						Poll::Ready(Some( Err(err) )) =>
+						{
+							self.buffer_read_error( err );
+							return Ok(have_read).into();
+						}
+
+						Poll::Pending =>
+						{
+							return Ok(have_read).into();
+						}
+					}
+				}
+			}
+
+		}} // loop, match
+	}
+
+
+	// Try to fill as many buffers as possible. Only go the next buffer if the current one is full.
+	//
+	// If poll_read returns Pending and we already have data, we can ignore it.
+	// If poll_read returns an error and we already have data, we store it for the next call.
+	//
+	fn poll_read_vectored_impl( mut self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &mut [IoSliceMut<'_>] ) -> Poll< io::Result<usize> >
+	where
+
+		St: TryStream< Ok=I, Error=io::Error >,
+		I: AsRef<[u8]>,
+
+	{
+		let mut have_read = 0;
+
+		for b in bufs
+		{
+			if !b.is_empty()
+			{
+				// calling with the first buffer, either poll_read fills it completely, or we return.
+				//
+				if have_read == 0
+				{
+					match ready!( self.as_mut().poll_read_impl( cx, &mut &mut **b ) )
+					{
+						// order matters
+						//
+						Err(e)                => return Poll::Ready( Err(e) ) ,
+						Ok (n) if n < b.len() => return Poll::Ready( Ok (n) ) , // stream is pending or ended
+
+						Ok(n) => // we filled entire buffer, we can pass to the next one
+						{
+							debug_assert!( n == b.len() );
+							have_read += n;
+						}
+					}
+				}
+
+				else // have_read != 0, this not the first buffer
 				{
+					// A queued error or the end of the stream is for the next call.
+					//
+					if matches!( self.state.front(), Some( ReadState::Error{..} | ReadState::Eof ) )
+					{
+						return Poll::Ready( Ok(have_read) );
+					}
+
 					// We won't be able to return pending as we already have data, so make sure
 					// the stream doesn't try to wake up the task.
 					//
 					let     waker   = noop_waker();
 					let mut context = Context::from_waker( &waker );
 
-					match Pin::new( &mut self.inner ).try_poll_next( &mut context )
+					// either it fills the entire buffer, or we return.
+					//
+					match self.as_mut().poll_read_impl( &mut context, &mut &mut **b )
 					{
-						// We have an item. Store it and continue the loop.
-						//
-						Poll::Ready( Some(Ok( chunk )) ) =>
-
-							state = ReadState::Ready { chunk: Cursor::new(chunk) }.into(),
-
-
-						// The stream has ended
+						// order matters
 						//
-						Poll::Ready( None ) =>
-						{
-							// return whatever we had already read.
-							//
-							self.state = ReadState::Eof.into();
-							return Ok(have_read).into();
-						}
+						Poll::Pending                       => return Poll::Ready( Ok(have_read    ) ) ,
+						Poll::Ready( Ok(n) ) if n < b.len() => return Poll::Ready( Ok(have_read + n) ) , // stream is pending or ended
 
-						Poll::Ready(Some( Err(err) )) =>
+						Poll::Ready( Ok(n) ) => // we filled entire buffer, we can pass to the next one
 						{
-							self.state = ReadState::Error{ error: err }.into();
-							return Ok(have_read).into();
+							debug_assert!( n == b.len() );
+							have_read += n;
 						}
 
-						Poll::Pending =>
+						Poll::Ready( Err(e) ) =>
 						{
-							self.state = None;
-							return Ok(have_read).into();
+							// store the error for next time, because we have to return have_read first.
+							//
+							self.buffer_read_error( e );
+							return Poll::Ready( Ok(have_read) );
 						}
 					}
 				}
 			}
+		}
 
-		}} // loop, match
+		// Either all buffers where zero length, or we filled all of them.
+		// I'm not sure what the point is of polling the stream if we just get an empty buffer,
+		// but it's what the default impls in std and futures do, so let's be consistent.
+		//
+		if   have_read == 0 { self.poll_read_impl( cx, &mut &mut [][..] ) }
+		else                { Poll::Ready( Ok(have_read) )       }
 	}
 
 
-	// Try to fill as many buffers as possible. Only go the next buffer if the current one is full.
+	// Without a write buffer, every call creates exactly one item from all the data passed in.
 	//
-	// If poll_read returns Pending and we already have data, we can ignore it.
-	// If poll_read returns an error and we already have data, we store it for the next call.
+	// With a write buffer, data is accumulated until the buffer is full, and then the buffer is sent
+	// as one item. Data that would fill an empty buffer by itself is sent directly, like BufWriter does.
 	//
-	fn poll_read_vectored_impl( mut self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &mut [IoSliceMut<'_>] ) -> Poll< io::Result<usize> >
+	fn poll_write_impl( mut self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &[ IoSlice<'_> ] ) -> Poll< io::Result<usize> >
+	where
+
+		St: Sink< I, Error=io::Error >,
+		I: From< Vec<u8> >,
+
+	{
+		self.poll_pending_flush( cx );
+
+		if let Some( e ) = self.write_err.take()
+		{
+			return Poll::Ready( Err(e) );
+		}
+
+		let capacity = self.write_capacity();
+
+		// The buffer is full, so we have to get rid of it before we can take more data. This is
+		// where backpressure from the sink reaches the writer. A buffer restored with from_parts can
+		// hold more than one item, so keep sending until there is room.
+		//
+		while self.write_buf.len() > self.write_tail() && self.write_buf.len() >= capacity
+		{
+			ready!( self.as_mut().poll_send_buffer( cx ) )?;
+		}
+
+
+		let     total: usize = bufs.iter().map( |buf| buf.len() ).sum();
+		let mut len          = total;
+		let     tail         = self.write_tail();
+
+		// In text mode, only take valid UTF-8.
+		//
+		if self.text.is_some()
+		{
+			len = text::valid_len( &self.write_buf[ self.write_buf.len() - tail.. ], bufs, total );
+
+			if len == 0 && total > 0
+			{
+				let err = io::Error::new( io::ErrorKind::InvalidData, "IoStream: invalid UTF-8 written in text mode" );
+
+				return Poll::Ready( Err(err) );
+			}
+		}
+
+
+		// In text mode, an incomplete code point can be left in the buffer. It becomes the start of
+		// the next item.
+		//
+		if self.write_buf.len() == tail && tail + len >= capacity
+		{
+			// Must call this before start_send
+			//
+			ready!( self.poll_inner_ready( cx ) )?;
+
+			// Don't take more than fits in one item, the caller will have to write the rest again.
+			//
+			let take = len.min( self.config.max_item_size - tail );
+
+			#[ cfg( feature = "map_pharos" ) ]
+			//
+			if take < len
+			{
+				self.notify( IoEvent::WriteSplit{ taken: take, len } );
+			}
+
+			if let Some( make ) = self.text
+			{
+				self.send_text( make, bufs, take )?;
+			}
+
+			else
+			{
+				let item = self.make_item( bufs, take );
+
+				self.send_item( item, take )?;
+			}
+
+			return Poll::Ready( Ok(take) );
+		}
+
+
+		let mut wrote = 0;
+
+		for buf in bufs
+		{
+			let n = buf.len().min( capacity.saturating_sub( self.write_buf.len() ) ).min( len - wrote );
+
+			self.write_buf.extend_from_slice( &buf[..n] );
+			wrote += n;
+
+			if n < buf.len() { break }
+		}
+
+		// The buffer is full, try to send it right away. We will return how many bytes we took,
+		// so make sure the sink doesn't try to wake up the task. If the sink isn't ready, the next
+		// write or flush will send it.
+		//
+		if self.write_buf.len() >= capacity && self.write_buf.len() > self.write_tail()
+		{
+			let     waker   = noop_waker();
+			let mut context = Context::from_waker( &waker );
+
+			if let Poll::Ready( Err(e) ) = self.as_mut().poll_send_buffer( &mut context )
+			{
+				self.defer_write_error( e );
+			}
+		}
+
+		Poll::Ready( Ok(wrote) )
+	}
+
+
+	// How many bytes we can buffer before we have to send an item.
+	//
+	fn write_capacity( &self ) -> usize
+	{
+		self.config.write_buffer.min( self.config.max_item_size )
+	}
+
+
+	// The length of an incomplete code point at the end of the write buffer in text mode. It can't
+	// be sent until the rest of it is written.
+	//
+	fn write_tail( &self ) -> usize
+	{
+		match self.text
+		{
+			Some(_) => text::incomplete_tail( &self.write_buf ),
+			None    => 0,
+		}
+	}
+
+
+	// Send the content of the write buffer as one item. If the buffer holds more than the maximum
+	// item size, which can happen when it was restored with from_parts, only send what fits.
+	//
+	fn poll_send_buffer( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
 	where
 
-		St: TryStream< Ok=I, Error=io::Error >,
-		I: AsRef<[u8]>,
+		St: Sink< I, Error=io::Error >,
+		I: From< Vec<u8> >,
+
+	{
+		// Must call this before start_send
+		//
+		ready!( self.poll_inner_ready( cx ) )?;
+
+		// Only send whole code points in text mode.
+		//
+		let end  = self.write_buf.len() - self.write_tail();
+		let take = match self.text
+		{
+			Some(_) => text::char_boundary( &self.write_buf[..end], self.config.max_item_size ),
+			None    => end.min( self.config.max_item_size ),
+		};
+
+		#[ cfg( feature = "map_pharos" ) ]
+		//
+		self.notify( IoEvent::WriteCoalesced{ len: take } );
+
+		if let Some( make ) = self.text
+		{
+			let rest = self.write_buf.split_off( take );
+			let item = mem::replace( &mut self.write_buf, rest );
+
+			return Poll::Ready( self.send_string( make, item ) );
+		}
+
+		#[ cfg( feature = "bytes" ) ]
+		//
+		if let Self{ arena: Some( arena ), write_buf, .. } = &mut *self
+		{
+			// Copy into the arena, so we can keep reusing the allocation of the write buffer.
+			//
+			let item = arena.make_item( &[ IoSlice::new( &write_buf[..take] ) ], take );
+
+			write_buf.drain( ..take );
+
+			return Poll::Ready( self.send_item( item, take ) );
+		}
+
+		let item = if self.write_buf.len() > take
+		{
+			let rest = self.write_buf.split_off( take );
+
+			mem::replace( &mut self.write_buf, rest )
+		}
+
+		else
+		{
+			let capacity = self.write_capacity();
+
+			mem::replace( &mut self.write_buf, Vec::with_capacity( capacity ) )
+		};
+
+		Poll::Ready( self.send_item( item.into(), take ) )
+	}
+
+
+	// Create an item from the first `take` bytes in bufs.
+	//
+	fn make_item( &mut self, bufs: &[ IoSlice<'_> ], take: usize ) -> I
+	where
+
+		I: From< Vec<u8> >,
+
+	{
+		#[ cfg( feature = "bytes" ) ]
+		//
+		if let Some( arena ) = &mut self.arena
+		{
+			return arena.make_item( bufs, take );
+		}
+
+		let mut item = Vec::with_capacity( take );
+
+		for buf in bufs
+		{
+			let n = buf.len().min( take - item.len() );
+
+			item.extend_from_slice( &buf[..n] );
+
+			if item.len() == take { break }
+		}
+
+		item.into()
+	}
+
+
+	// Text mode: send the incomplete code point in the write buffer followed by the first `take` bytes
+	// of bufs. A new incomplete code point at the end stays in the write buffer.
+	//
+	fn send_text( &mut self, make: TextFn<I>, bufs: &[ IoSlice<'_> ], take: usize ) -> io::Result<()>
+	where
+
+		St: Sink< I, Error=io::Error >,
+
+	{
+		let mut item = mem::take( &mut self.write_buf );
+		let mut left = take;
+
+		for buf in bufs
+		{
+			let n = buf.len().min( left );
+
+			item.extend_from_slice( &buf[..n] );
+			left -= n;
+
+			if left == 0 { break }
+		}
+
+		let end = item.len() - text::incomplete_tail( &item );
+
+		self.write_buf = item.split_off( end );
+
+		self.send_string( make, item )
+	}
+
+
+	// Send valid UTF-8 as an item created from a String. Nothing is sent if `bytes` is empty.
+	//
+	fn send_string( &mut self, make: TextFn<I>, bytes: Vec<u8> ) -> io::Result<()>
+	where
+
+		St: Sink< I, Error=io::Error >,
+
+	{
+		if bytes.is_empty() { return Ok(()) }
+
+		let len  = bytes.len();
+		let text = String::from_utf8( bytes ).map_err( |e| io::Error::new( io::ErrorKind::InvalidData, e ) )?;
+
+		self.send_item( make(text), len )
+	}
+
+
+	// Hand an item of `len` bytes to the sink. poll_ready must have returned Ready before calling this.
+	//
+	fn send_item( &mut self, item: I, len: usize ) -> io::Result<()>
+	where
+
+		St: Sink< I, Error=io::Error >,
+
+	{
+		Pin::new( &mut self.inner ).start_send( item )?;
+
+		self.sent( len );
+
+		let flush = match self.config.flush_policy
+		{
+			FlushPolicy::Always   => true                       ,
+			FlushPolicy::Never    => false                      ,
+			FlushPolicy::Items(n) => self.unflushed.items >= n  ,
+			FlushPolicy::Bytes(n) => self.unflushed.bytes >= n  ,
+		};
+
+		if !flush { return Ok(()) }
+
+		// Client code like futures-codec and tokio-codec turn a flush on their sink in a poll_write here.
+		// Combinators like CopyBufInto will only call flush after their entire input
+		// stream is exhausted. This is a problem if the source temporarily goes dry.
+		// Unless a write buffer was configured, we don't buffer here, but always create an entire
+		// websocket message from the buffer we get in poll_write, so there is no reason not to flush here.
+		// This means the burden is on the caller to call with a buffer of sufficient size
+		// to avoid perf problems, but there is BufReader and BufWriter in the futures library to
+		// help with that if necessary. Transports that batch items themselves can opt out with
+		// FlushPolicy.
+		//
+		// We can't return Pending from the flush, since we took the data and must return how many
+		// bytes we took. The client should not try to send this data again. We flush with a dummy
+		// context to avoid a spurious wakeup, and if the flush is pending, we remember that so the
+		// next call to poll_write, poll_flush, poll_close or any of the read methods drives it to
+		// completion with the real waker.
+		//
+		// So, flush!
+		//
+		let     waker   = noop_waker();
+		let mut context = Context::from_waker( &waker );
+
+		self.pending_flush = Some( <St as Sink<I>>::poll_flush );
+		self.poll_pending_flush( &mut context );
+
+		Ok(())
+	}
+
+
+	// Poll the sink for readiness, counting how often it's not ready.
+	//
+	fn poll_inner_ready( &mut self, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	where
+
+		St: Sink< I, Error=io::Error >,
+
+	{
+		let ready = Pin::new( &mut self.inner ).poll_ready( cx );
+
+		if ready.is_pending()
+		{
+			self.stats.ready_pending += 1;
+		}
+
+		ready
+	}
+
+
+	// Count an item of `len` bytes that was handed to the sink.
+	//
+	fn sent( &mut self, len: usize )
+	{
+		self.unflushed.items += 1;
+		self.unflushed.bytes += len;
+
+		self.stats.written( len );
+	}
+
+
+	// A flush of the sink completed.
+	//
+	fn flushed( &mut self )
+	{
+		self.unflushed      = Unflushed::default();
+		self.stats.flushes += 1;
+	}
+
+
+	// Keep an error from the stream to return it from the next read, because the current one already
+	// has data to return. It goes after anything that is already queued.
+	//
+	fn buffer_read_error( &mut self, error: io::Error )
+	{
+		#[ cfg( feature = "map_pharos" ) ]
+		//
+		self.notify( IoEvent::ReadErrorBuffered{ kind: error.kind() } );
+
+		self.stats.read_errors += 1;
+		self.state.push_back( ReadState::Error{ error } );
+	}
+
 
+	// Keep an error from the sink to return it from the next write, because the data it happened on
+	// was already taken.
+	//
+	fn defer_write_error( &mut self, error: io::Error )
 	{
-		let mut have_read = 0;
+		#[ cfg( feature = "map_pharos" ) ]
+		//
+		self.notify( IoEvent::WriteErrorDeferred{ kind: error.kind() } );
 
-		for b in bufs
-		{
-			if !b.is_empty()
-			{
-				// calling with the first buffer, either poll_read fills it completely, or we return.
-				//
-				if have_read == 0
-				{
-					match ready!( self.as_mut().poll_read_impl( cx, b ) )
-					{
-						// order matters
-						//
-						Err(e)                => return Poll::Ready( Err(e) ) ,
-						Ok (n) if n < b.len() => return Poll::Ready( Ok (n) ) , // stream is pending or ended
+		self.stats.write_errors += 1;
+		self.write_err           = error.into();
+	}
 
-						Ok(n) => // we filled entire buffer, we can pass to the next one
-						{
-							debug_assert!( n == b.len() );
-							have_read += n;
-						}
-					}
-				}
 
-				else // have_read != 0, this not the first buffer
-				{
-					// We won't be able to return pending as we already have data, so make sure
-					// the stream doesn't try to wake up the task.
-					//
-					let     waker   = noop_waker();
-					let mut context = Context::from_waker( &waker );
+	// Drive an implicit flush that returned pending before. When it is still pending, the waker from
+	// `cx` will be woken when it can make progress. An error is returned from the next write.
+	//
+	fn poll_pending_flush( &mut self, cx: &mut Context<'_> )
+	{
+		let Some( flush ) = self.pending_flush else { return };
 
-					// either it fills the entire buffer, or we return.
-					//
-					match self.as_mut().poll_read_impl( &mut context, b )
-					{
-						// order matters
-						//
-						Poll::Pending                       => return Poll::Ready( Ok(have_read    ) ) ,
-						Poll::Ready( Ok(n) ) if n < b.len() => return Poll::Ready( Ok(have_read + n) ) , // stream is pending or ended
+		match flush( Pin::new( &mut self.inner ), cx )
+		{
+			Poll::Pending => {}
 
-						Poll::Ready( Ok(n) ) => // we filled entire buffer, we can pass to the next one
-						{
-							debug_assert!( n == b.len() );
-							have_read += n;
-						}
+			Poll::Ready( Ok(_) ) =>
+			{
+				self.pending_flush = None;
+				self.flushed();
+			}
 
-						Poll::Ready( Err(e) ) =>
-						{
-							// store the error for next time, because we have to return have_read first.
-							//
-							self.state = ReadState::Error{ error: e }.into();
-							return Poll::Ready( Ok(have_read) );
-						}
-					}
-				}
+			Poll::Ready( Err(e) ) =>
+			{
+				self.pending_flush = None;
+				self.defer_write_error( e );
 			}
 		}
-
-		// Either all buffers where zero length, or we filled all of them.
-		// I'm not sure what the point is of polling the stream if we just get an empty buffer,
-		// but it's what the default impls in std and futures do, so let's be consistent.
-		//
-		if   have_read == 0 { self.poll_read_impl( cx, &mut [] ) }
-		else                { Poll::Ready( Ok(have_read) )       }
 	}
 
 
-	fn poll_write_impl<'a>( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &'a [u8] ) -> Poll< io::Result<usize> >
+
+	fn poll_flush_impl(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll< io::Result<()> >
 	where
 
 		St: Sink< I, Error=io::Error >,
 		I: From< Vec<u8> >,
 
 	{
-		if let Some( e ) = self.write_err.take()
+		while self.write_buf.len() > self.write_tail()
 		{
-			return Poll::Ready( Err(e) );
+			ready!( self.as_mut().poll_send_buffer( cx ) )?;
 		}
 
-		// Must call this before start_send
-		//
-		let res = ready!( Pin::new( &mut self.inner ).poll_ready(cx) );
-
-		if let Err( e ) = res
-		{
-			return Poll::Ready( Err(e) );
-		}
+		let result = ready!( Pin::new( &mut self.inner ).poll_flush(cx) );
 
+		// Whatever the outcome, it is also the outcome of an implicit flush that was pending.
+		//
+		self.pending_flush = None;
 
-		match Pin::new( &mut self.inner ).start_send( buf.to_vec().into() )
+		match result
 		{
 			Ok (_) =>
 			{
-				// Client code like futures-codec and tokio-codec turn a flush on their sink in a poll_write here.
-				// Combinators like CopyBufInto will only call flush after their entire input
-				// stream is exhausted. This is a problem if the source temporarily goes dry.
-				// We actually don't buffer here, but always create an entire websocket message from the
-				// buffer we get in poll_write, so there is no reason not to flush here.
-				// This means the burden is on the caller to call with a buffer of sufficient size
-				// to avoid perf problems, but there is BufReader and BufWriter in the futures library to
-				// help with that if necessary.
-				//
-				// We will ignore the Pending return from the flush, since we took the data and
-				// must return how many bytes we took. The client should not try to send this data again.
-				// This does mean there might be a spurious wakeup, TODO: we should test that.
-				// We could supply a dummy context to avoid the wakup.
-				//
-				// So, flush!
-				//
-				let     waker   = noop_waker();
-				let mut context = Context::from_waker( &waker );
-
-				match Pin::new( &mut self.inner ).poll_flush( &mut context )
-				{
-					Poll::Pending         | // ignore
-					Poll::Ready( Ok(_) ) => {}
-
-					Poll::Ready( Err(e)) => self.write_err = e.into(),
-				}
-
-
-				Poll::Ready(Ok( buf.len() ))
+				self.flushed();
+				Poll::Ready(Ok( () ))
 			}
 
-			Err(e) => Poll::Ready( Err(e) ),
+			Err(e) => Poll::Ready(Err( e )),
 		}
 	}
 
 
-	fn poll_write_vectored_impl<'a>( mut self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &'a[ IoSlice<'a> ] ) -> Poll< io::Result<usize> >
+	fn poll_close_impl( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
 	where
 
 		St: Sink< I, Error=io::Error >,
 		I: From< Vec<u8> >,
 
 	{
-		if let Some( e ) = self.write_err.take()
+		while self.write_buf.len() > self.write_tail()
 		{
-			return Poll::Ready( Err(e) );
+			ready!( self.as_mut().poll_send_buffer( cx ) )?;
 		}
 
-
-		// Must call this before start_send
+		// In text mode, the code point will never be completed.
 		//
-		let res = ready!( Pin::new( &mut self.inner ).poll_ready(cx) );
-
-		if let Err( e ) = res
+		if !self.write_buf.is_empty()
 		{
-			return Poll::Ready( Err(e) )
+			self.write_buf.clear();
+
+			let err = io::Error::new( io::ErrorKind::InvalidData, "IoStream: closed in the middle of a UTF-8 code point" );
+
+			return Poll::Ready( Err(err) );
 		}
 
+		let result = ready!( Pin::new( &mut self.inner ).poll_close( cx ) );
 
-		let mut wrote = 0;
+		self.pending_flush = None;
 
-		for buf in bufs { wrote += buf.len(); }
+		result.into()
+	}
 
-		let mut item = Vec::with_capacity( wrote );
 
-		for buf in bufs
-		{
-			item.extend_from_slice( buf );
-		}
+	// Shared by the futures and tokio AsyncBufRead impls.
+	//
+	fn poll_fill_buf_impl( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<&[u8]> >
+	where
+
+		St: TryStream< Ok=I, Error=io::Error >,
+		I: AsRef<[u8]>,
+
+	{
+		self.poll_pending_flush( cx );
 
+		// An empty slice means the end of the stream, so we can't return one for an empty item. Skip
+		// them, as well as chunks that were read entirely.
+		//
+		self.skip_read_chunks();
 
-		match Pin::new( &mut self.inner ).start_send( item.into() )
+		while self.state.is_empty()
 		{
-			Ok (_) =>
+			match ready!( self.as_mut().poll_stream(cx) )
 			{
-				// Client code like futures-codec and tokio-codec turn a flush on their sink in a poll_write here.
-				// Combinators like CopyBufInto will only call flush after their entire input
-				// stream is exhausted. This is a problem if the source temporarily goes dry.
-				// We actually don't buffer here, but always create an entire websocket message from the
-				// buffer we get in poll_write, so there is no reason not to flush here.
-				// This means the burden is on the caller to call with a buffer of sufficient size
-				// to avoid perf problems, but there is BufReader and BufWriter in the futures library to
-				// help with that if necessary.
-				//
-				// We will ignore the Pending return from the flush, since we took the data and
-				// must return how many bytes we took. The client should not try to send this data again.
-				// This does mean there might be a spurious wakeup, TODO: we should test that.
-				// We could supply a dummy context to avoid the wakup.
-				//
-				// So, flush!
-				//
-				let     waker   = noop_waker();
-				let mut context = Context::from_waker( &waker );
-
-				match Pin::new( &mut self.inner ).poll_flush( &mut context )
+				Some( Ok(chunk) ) =>
 				{
-					Poll::Pending         | // ignore
-					Poll::Ready( Ok(_) ) => {}
+					if !chunk.as_ref().is_empty()
+					{
+						self.state.push_back( ReadState::Ready
+						{
+							chunk: Cursor::new( chunk ),
+						});
+					}
+				}
 
-					Poll::Ready( Err(e)) => self.write_err = e.into(),
+				Some( Err(error) ) =>
+				{
+					self.state.push_back( ReadState::Error{ error } );
 				}
 
-				Poll::Ready(Ok( wrote ))
+				None =>
+				{
+					self.state.push_back( ReadState::Eof );
+				}
 			}
-
-			Err(e) => Poll::Ready( Err(e) ),
 		}
-	}
 
 
+		// Merge small items up to the minimum fill size.
+		//
+		if let Some(( min, make )) = self.min_fill
+		{
+			if matches!( self.state.front(), Some( ReadState::Ready{..} ) )
+			{
+				self.as_mut().take_ready_items( |this| this.ready_len() < min );
+				self.merge_ready( min, make );
+			}
+		}
+
 
-	fn poll_flush_impl(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll< io::Result<()> >
-	where
+		// Only an error is taken out, the chunk and the end of the stream stay for the next call.
+		//
+		match self.state.pop_front()
+		{
+			Some( ReadState::Error{ error } ) => return Poll::Ready( Err(error) ),
+			Some( other                     ) => self.state.push_front( other ) ,
+			None                              => {}
+		}
 
-		St: Sink< I, Error=io::Error >
-	{
-		match ready!( Pin::new( &mut self.inner ).poll_flush(cx) )
+		match self.get_mut().state.front_mut()
 		{
-			Ok (_) => Poll::Ready(Ok ( () )) ,
-			Err(e) => Poll::Ready(Err( e  )) ,
+			Some( ReadState::Ready{ chunk } ) => Poll::Ready( chunk.fill_buf() ),
+			_                                 => Poll::Ready( Ok(&[])          ),
 		}
 	}
 
 
-	fn poll_close_impl( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+
+	// Consuming more than the current chunk holds only consumes the chunk, like BufReader does.
+	// Consuming when there is no chunk does nothing.
+	//
+	fn consume_impl( mut self: Pin<&mut Self>, amount: usize )
 	where
 
-		St: Sink< I, Error=io::Error >
+		I: AsRef<[u8]>,
+
 	{
-		ready!( Pin::new( &mut self.inner ).poll_close( cx ) ).into()
+		if amount == 0 { return }
+
+		if let Some( ReadState::Ready{ chunk } ) = self.state.front_mut()
+		{
+			let len = chunk.get_ref().as_ref().len() as u64;
+			let pos = chunk.position().saturating_add( amount as u64 ).min( len );
+
+			chunk.set_position( pos );
+
+			// if we are at the end, remove the chunk
+			//
+			if pos == len
+			{
+				self.state.pop_front();
+			}
+		}
 	}
 }
 
 
 
-impl<St: Unpin, I> fmt::Debug for IoStream<St, I>
+/// Shows the type of the inner stream and what is queued on the read side: how many entries, the
+/// kind of the first buffered error and whether the stream has ended. Use [`IoStream::state`] for
+/// the number of buffered bytes.
+//
+impl<St, I> fmt::Debug for IoStream<St, I>
+where
+
+	St: Unpin,
+
 {
 	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
 	{
-		write!( f, "IoStream over Tungstenite" )
+		let error = self.state.iter().find_map( |entry| match entry
+		{
+			ReadState::Error{ error } => Some( error.kind() ),
+			_                         => None                ,
+		});
+
+		let eof = self.state.iter().any( |entry| matches!( entry, ReadState::Eof ) );
+
+		f.debug_struct( "IoStream" )
+
+			.field( "inner" , &format_args!( "{}", any::type_name::<St>() ) )
+			.field( "queued", &self.state.len()                              )
+			.field( "error" , &error                                         )
+			.field( "eof"   , &eof                                           )
+			.finish_non_exhaustive()
 	}
 }
+
+
 /// ### Errors
 ///
 /// The following errors can be returned when writing to the stream:
@@ -524,6 +1871,7 @@ impl<St: Unpin, I> fmt::Debug for IoStream<St, I>
 /// - [`io::ErrorKind::InvalidData`]: This means that a tungstenite::error::Capacity occurred. This means that
 ///   you send in a buffer bigger than the maximum message size configured on the underlying websocket connection.
 ///   If you did not set it manually, the default for tungstenite is 64MB.
+///   Use [`IoStream::with_max_item_size`] to avoid this.
 ///
 /// - other std::io::Error's generally mean something went wrong on the underlying transport. Consider these fatal
 ///   and just drop the connection.
@@ -535,18 +1883,20 @@ where
 	I: From< Vec<u8> >
 
 {
-	/// Will always flush the underlying socket. Will always create an entire Websocket message from every write,
-	/// so call with a sufficiently large buffer if you have performance problems.
+	/// Every write becomes one item, unless [`IoStream::with_write_buffer`] gathers small writes into
+	/// bigger items. [`IoStream::with_max_item_size`] limits the size of an item, so a bigger write is
+	/// a short write. When the sink is flushed after sending depends on [`IoStream::with_flush_policy`],
+	/// by default it is flushed after every item.
 	//
 	fn poll_write( self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8] ) -> Poll< io::Result<usize> >
 	{
-		self.poll_write_impl( cx, buf )
+		self.poll_write_impl( cx, &[ IoSlice::new( buf ) ] )
 	}
 
 
 	fn poll_write_vectored( self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &[ IoSlice<'_> ] ) -> Poll< io::Result<usize> >
 	{
-		self.poll_write_vectored_impl( cx, bufs )
+		self.poll_write_impl( cx, bufs )
 	}
 
 
@@ -577,7 +1927,21 @@ where
 {
 	fn poll_write( self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8] ) -> Poll< io::Result<usize> >
 	{
-		self.poll_write_impl( cx, buf )
+		self.poll_write_impl( cx, &[ IoSlice::new( buf ) ] )
+	}
+
+
+	/// Creates one item from all buffers, like the futures impl does.
+	//
+	fn poll_write_vectored( self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &[ IoSlice<'_> ] ) -> Poll< io::Result<usize> >
+	{
+		self.poll_write_impl( cx, bufs )
+	}
+
+
+	fn is_write_vectored( &self ) -> bool
+	{
+		true
 	}
 
 
@@ -603,13 +1967,15 @@ where
 	I: AsRef<[u8]>
 
 {
-	fn poll_read( self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8] ) -> Poll< io::Result<usize> >
+	fn poll_read( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8] ) -> Poll< io::Result<usize> >
 	{
-		self.poll_read_impl( cx, buf )
+		self.poll_pending_flush( cx );
+		self.poll_read_impl( cx, &mut &mut *buf )
 	}
 
-	fn poll_read_vectored( self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &mut [IoSliceMut<'_>] ) -> Poll< io::Result<usize> >
+	fn poll_read_vectored( mut self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &mut [IoSliceMut<'_>] ) -> Poll< io::Result<usize> >
 	{
+		self.poll_pending_flush( cx );
 		self.poll_read_vectored_impl( cx, bufs )
 	}
 }
@@ -628,10 +1994,13 @@ where
 	I: AsRef<[u8]>
 
 {
-	fn poll_read( self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut tokio::io::ReadBuf<'_> ) -> Poll< io::Result<()> >
+	fn poll_read( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut tokio::io::ReadBuf<'_> ) -> Poll< io::Result<()> >
 	{
-		let read_count = ready!( self.poll_read_impl( cx, buf.initialize_unfilled() ) )?;
-		buf.advance( read_count );
+		self.poll_pending_flush( cx );
+
+		// ReadBuf::put_slice advances the filled part, and only initializes what we copy into it.
+		//
+		ready!( self.poll_read_impl( cx, buf ) )?;
 
 		Poll::Ready( Ok(()) )
 	}
@@ -639,6 +2008,33 @@ where
 
 
 
+#[ cfg( feature = "tokio_io" ) ]
+//
+#[ cfg_attr( nightly, doc(cfg( feature = "tokio_io" )) ) ]
+//
+/// This impl requires the `tokio_io` feature. It behaves like the futures impl.
+//
+impl<St, I> TokAsyncBufRead for IoStream<St, I>
+where
+
+	St: TryStream< Ok=I, Error=io::Error > + Unpin,
+	I: AsRef<[u8]>
+
+{
+	fn poll_fill_buf( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<&[u8]> >
+	{
+		self.poll_fill_buf_impl( cx )
+	}
+
+
+	fn consume( self: Pin<&mut Self>, amount: usize )
+	{
+		self.consume_impl( amount )
+	}
+}
+
+
+
 
 #[ cfg( feature = "map_pharos" ) ]
 //
@@ -661,9 +2057,7 @@ where
 	{
 		async move
 		{
-			let result = self.inner.observe( options ).await;
-
-			result.map_err( Into::into )
+			self.inner.observe( options ).await
 
 		}.boxed()
 	}
@@ -691,9 +2085,7 @@ where
 	{
 		async move
 		{
-			let result = self.inner.observe_local( options ).await;
-
-			result.map_err( Into::into )
+			self.inner.observe_local( options ).await
 
 		}.boxed_local()
 	}
@@ -730,6 +2122,10 @@ where
 
 
 
+/// Empty items are skipped, since an empty buffer means the end of the stream. Consuming more than
+/// the buffer returned by `poll_fill_buf` only consumes that buffer, like [`futures::io::BufReader`]
+/// does.
+//
 impl<St, I> AsyncBufRead for IoStream<St, I>
 where
 
@@ -737,92 +2133,15 @@ where
 	I : AsRef<[u8]> + Unpin                        ,
 
 {
-	fn poll_fill_buf( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<&[u8]> >
+	fn poll_fill_buf( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<&[u8]> >
 	{
-		if self.state.is_none()
-		{
-			match ready!( Pin::new( &mut self.inner ).try_poll_next(cx) )
-			{
-				Some( Ok(chunk) ) =>
-				{
-					if !chunk.as_ref().is_empty()
-					{
-						self.state = ReadState::Ready
-						{
-							chunk: Cursor::new( chunk ),
-
-						}.into();
-					}
-				}
-
-				Some( Err(error) ) =>
-				{
-					self.state = ReadState::Error{ error }.into();
-				}
-
-				None =>
-				{
-					self.state = ReadState::Eof.into();
-				}
-			}
-		}
-
-
-		match self.state.take()
-		{
-			Some( ReadState::Error{ error } ) =>
-			{
-				self.state = None;
-				Poll::Ready( Err(error) )
-			}
-
-			Some( ReadState::Eof ) =>
-			{
-				Poll::Ready( Ok(&[]) )
-			}
-
-			Some(x) =>
-			{
-				// Put it back, because we will return a reference to the buffer.
-				//
-				self.state = Some(x);
-
-				if let Some( ReadState::Ready{ ref mut chunk } ) = self.get_mut().state
-				{
-					return Poll::Ready( chunk.fill_buf() );
-				}
-
-				unreachable!();
-			}
-
-			None => unreachable!(),
-		}
+		self.poll_fill_buf_impl( cx )
 	}
 
 
-
-	fn consume( mut self: Pin<&mut Self>, amount: usize )
+	fn consume( self: Pin<&mut Self>, amount: usize )
 	{
-		if amount == 0 { return }
-
-		if let Some( ReadState::Ready{ chunk } ) = &mut self.state
-		{
-			chunk.consume( amount );
-
-			// if we are at the end, remove the chunk
-			//
-			match chunk.get_ref().as_ref().len() as u64
-			{
-				x if x == chunk.position() => self.state = None,
-				x if x  < chunk.position() => debug_assert!( false, "Attempted to consume more than available bytes" ),
-				_                          => {}
-			}
-		}
-
-		else
-		{
-			debug_assert!( false, "Attempted to consume from IntoAsyncRead without chunk" );
-		}
+		self.consume_impl( amount )
 	}
 }
 
@@ -0,0 +1,473 @@
+use
+{
+	crate   :: { IoStream, Unflushed, FlushFn, Stats                         } ,
+	std     :: { fmt, error::Error, io::{ self, IoSlice, IoSliceMut }        } ,
+	std     :: { collections::VecDeque                                       } ,
+	std     :: { pin::Pin, task::{ Poll, Context }                           } ,
+	futures :: { Stream, StreamExt, Sink, AsyncRead, AsyncWrite, AsyncBufRead } ,
+	futures :: { stream::{ SplitStream, SplitSink }                          } ,
+};
+
+
+#[ cfg( feature = "bytes" ) ]
+//
+use crate::Arena;
+
+#[ cfg( feature = "tokio_io" ) ]
+//
+use tokio::io::{ AsyncRead as TokAsyncRead, AsyncWrite as TokAsyncWrite, AsyncBufRead as TokAsyncBufRead };
+
+
+
+impl<St, I> IoStream<St, I>
+where
+
+	St: Stream< Item=io::Result<I> > + Sink< I, Error=io::Error > + Unpin,
+
+{
+	/// Split the IoStream in a read half and a write half, so reading and writing can happen
+	/// concurrently from different tasks. The inner stream is shared between the halves with a
+	/// lock-free `BiLock` as in [`StreamExt::split`].
+	///
+	/// Buffered read data and errors go to the [`IoReadHalf`], a deferred write error and the
+	/// write buffer go to the [`IoWriteHalf`]. Use [`IoReadHalf::reunite`] to get the IoStream back.
+	//
+	pub fn split( self ) -> ( IoReadHalf<St, I>, IoWriteHalf<St, I> )
+	{
+		let IoStream
+		{
+			inner         ,
+			state         ,
+			write_err     ,
+			write_buf     ,
+			unflushed     ,
+			pending_flush ,
+			text          ,
+			min_fill      ,
+			stats         ,
+			config        ,
+
+			#[ cfg( feature = "bytes" ) ]
+			//
+			arena,
+
+			#[ cfg( feature = "map_pharos" ) ]
+			//
+			pharos,
+
+		} = self;
+
+		let (sink, stream) = inner.split();
+
+		let read = IoStream
+		{
+			inner        : stream               ,
+			state                               ,
+			write_err    : None                 ,
+			write_buf    : Vec::new()           ,
+			unflushed    : Unflushed::default() ,
+			pending_flush: None                 ,
+			text         : None                 ,
+			min_fill                            ,
+			stats                               ,
+			config                              ,
+
+			#[ cfg( feature = "bytes" ) ]
+			//
+			arena: arena.as_ref().map( Arena::fork ),
+
+			#[ cfg( feature = "map_pharos" ) ]
+			//
+			pharos: pharos.clone(),
+		};
+
+		let write = IoStream
+		{
+			inner        : sink            ,
+			state        : VecDeque::new() ,
+			write_err                      ,
+			write_buf                      ,
+			unflushed                      ,
+			text                           ,
+			min_fill     : None            ,
+			stats                          ,
+			config                         ,
+
+			// An implicit flush that was pending continues on the shared sink.
+			//
+			pending_flush: pending_flush.map::< FlushFn<_>, _ >( |_| < SplitSink<St, I> as Sink<I> >::poll_flush ),
+
+			#[ cfg( feature = "bytes" ) ]
+			//
+			arena,
+
+			#[ cfg( feature = "map_pharos" ) ]
+			//
+			pharos,
+		};
+
+		( IoReadHalf{ inner: read }, IoWriteHalf{ inner: write } )
+	}
+}
+
+
+
+/// The read half of an [`IoStream`], obtained from [`IoStream::split`].
+///
+/// Implements [`AsyncRead`] and [`AsyncBufRead`], as well as [`tokio::io::AsyncRead`] and
+/// [`tokio::io::AsyncBufRead`] when the `tokio_io` feature is enabled.
+//
+pub struct IoReadHalf<St, I>
+{
+	inner: IoStream< SplitStream<St>, I >,
+}
+
+
+/// The write half of an [`IoStream`], obtained from [`IoStream::split`].
+///
+/// Implements [`AsyncWrite`], as well as [`tokio::io::AsyncWrite`] when the `tokio_io` feature
+/// is enabled.
+//
+pub struct IoWriteHalf<St, I>
+{
+	inner: IoStream< SplitSink<St, I>, I >,
+}
+
+
+
+impl<St, I> IoReadHalf<St, I>
+{
+	/// Returns `true` if both halves originate from the same call to [`IoStream::split`].
+	//
+	pub fn is_pair_of( &self, other: &IoWriteHalf<St, I> ) -> bool
+	{
+		self.inner.inner().is_pair_of( other.inner.inner() )
+	}
+
+
+	/// The read counters of [`IoStream::stats`], including those from before the split. The write
+	/// counters are zero, they are kept by the write half.
+	//
+	pub fn stats( &self ) -> Stats
+	{
+		Stats::join( self.inner.stats(), Stats::default() )
+	}
+
+
+	/// Put both halves back together. Fails if they don't originate from the same call to
+	/// [`IoStream::split`], in which case both halves are returned in the error.
+	///
+	/// Make sure to flush the write half first. An item that the write half has not yet handed
+	/// to the inner stream is lost, as with [`SplitStream::reunite`].
+	//
+	// The halves are handed back by value, like futures does for SplitStream.
+	//
+	#[ allow( clippy::result_large_err ) ]
+	//
+	pub fn reunite( self, other: IoWriteHalf<St, I> ) -> Result< IoStream<St, I>, ReuniteError<St, I> >
+	where
+
+		St: Sink< I, Error=io::Error > + Unpin,
+
+	{
+		if !self.is_pair_of( &other )
+		{
+			return Err( ReuniteError( self, other ) );
+		}
+
+		// The write side state of the read half is never used, and vice versa.
+		//
+		let IoStream{ inner: stream, state, min_fill, config, stats: read_stats, .. } = self.inner;
+
+		let IoStream
+		{
+			inner: sink   ,
+			write_err     ,
+			write_buf     ,
+			unflushed     ,
+			pending_flush ,
+			text          ,
+			stats         ,
+
+			#[ cfg( feature = "bytes" ) ]
+			//
+			arena,
+
+			#[ cfg( feature = "map_pharos" ) ]
+			//
+			pharos,
+			..
+
+		} = other.inner;
+
+		let inner = stream.reunite( sink )
+
+			.unwrap_or_else( |_| unreachable!( "checked that the halves form a pair" ) );
+
+		Ok( IoStream
+		{
+			inner     ,
+			state     ,
+			write_err ,
+			write_buf ,
+			unflushed ,
+			text      ,
+			min_fill  ,
+			config    ,
+
+			stats: Stats::join( read_stats, stats ),
+
+			pending_flush: pending_flush.map::< FlushFn<_>, _ >( |_| <St as Sink<I>>::poll_flush ),
+
+			#[ cfg( feature = "bytes" ) ]
+			//
+			arena,
+
+			// Both halves share the same observers.
+			//
+			#[ cfg( feature = "map_pharos" ) ]
+			//
+			pharos,
+		})
+	}
+}
+
+
+
+impl<St, I> IoWriteHalf<St, I>
+{
+	/// Returns `true` if both halves originate from the same call to [`IoStream::split`].
+	//
+	pub fn is_pair_of( &self, other: &IoReadHalf<St, I> ) -> bool
+	{
+		other.is_pair_of( self )
+	}
+
+
+	/// The write counters of [`IoStream::stats`], including those from before the split. The read
+	/// counters are zero, they are kept by the read half.
+	//
+	pub fn stats( &self ) -> Stats
+	{
+		Stats::join( Stats::default(), self.inner.stats() )
+	}
+
+
+	/// Put both halves back together. See [`IoReadHalf::reunite`].
+	//
+	#[ allow( clippy::result_large_err ) ]
+	//
+	pub fn reunite( self, other: IoReadHalf<St, I> ) -> Result< IoStream<St, I>, ReuniteError<St, I> >
+	where
+
+		St: Sink< I, Error=io::Error > + Unpin,
+
+	{
+		other.reunite( self )
+	}
+}
+
+
+
+impl<St, I> fmt::Debug for IoReadHalf<St, I>
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		f.debug_tuple( "IoReadHalf" ).field( &self.inner ).finish()
+	}
+}
+
+
+impl<St, I> fmt::Debug for IoWriteHalf<St, I>
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		f.debug_tuple( "IoWriteHalf" ).field( &self.inner ).finish()
+	}
+}
+
+
+
+/// Error returned when trying to reunite an [`IoReadHalf`] and an [`IoWriteHalf`] that don't
+/// originate from the same [`IoStream`]. Both halves are handed back.
+//
+pub struct ReuniteError<St, I>( pub IoReadHalf<St, I>, pub IoWriteHalf<St, I> );
+
+
+impl<St, I> fmt::Debug for ReuniteError<St, I>
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		f.debug_tuple( "ReuniteError" ).field( &"..." ).finish()
+	}
+}
+
+
+impl<St, I> fmt::Display for ReuniteError<St, I>
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "tried to reunite an IoReadHalf and IoWriteHalf that don't form a pair" )
+	}
+}
+
+
+impl<St, I> Error for ReuniteError<St, I> {}
+
+
+
+impl<St, I> AsyncRead for IoReadHalf<St, I>
+where
+
+	St: Stream< Item=io::Result<I> >,
+	I : AsRef<[u8]>,
+
+{
+	fn poll_read( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8] ) -> Poll< io::Result<usize> >
+	{
+		AsyncRead::poll_read( Pin::new( &mut self.inner ), cx, buf )
+	}
+
+	fn poll_read_vectored( mut self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &mut [IoSliceMut<'_>] ) -> Poll< io::Result<usize> >
+	{
+		AsyncRead::poll_read_vectored( Pin::new( &mut self.inner ), cx, bufs )
+	}
+}
+
+
+
+impl<St, I> AsyncBufRead for IoReadHalf<St, I>
+where
+
+	St: Stream< Item=io::Result<I> >,
+	I : AsRef<[u8]> + Unpin,
+
+{
+	fn poll_fill_buf( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<&[u8]> >
+	{
+		AsyncBufRead::poll_fill_buf( Pin::new( &mut self.get_mut().inner ), cx )
+	}
+
+	fn consume( mut self: Pin<&mut Self>, amount: usize )
+	{
+		AsyncBufRead::consume( Pin::new( &mut self.inner ), amount )
+	}
+}
+
+
+
+#[ cfg( feature = "tokio_io" ) ]
+//
+#[ cfg_attr( nightly, doc(cfg( feature = "tokio_io" )) ) ]
+//
+/// This impl requires the `tokio_io` feature.
+//
+impl<St, I> TokAsyncRead for IoReadHalf<St, I>
+where
+
+	St: Stream< Item=io::Result<I> >,
+	I : AsRef<[u8]>,
+
+{
+	fn poll_read( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut tokio::io::ReadBuf<'_> ) -> Poll< io::Result<()> >
+	{
+		TokAsyncRead::poll_read( Pin::new( &mut self.inner ), cx, buf )
+	}
+}
+
+
+
+#[ cfg( feature = "tokio_io" ) ]
+//
+#[ cfg_attr( nightly, doc(cfg( feature = "tokio_io" )) ) ]
+//
+/// This impl requires the `tokio_io` feature.
+//
+impl<St, I> TokAsyncBufRead for IoReadHalf<St, I>
+where
+
+	St: Stream< Item=io::Result<I> >,
+	I : AsRef<[u8]>,
+
+{
+	fn poll_fill_buf( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<&[u8]> >
+	{
+		TokAsyncBufRead::poll_fill_buf( Pin::new( &mut self.get_mut().inner ), cx )
+	}
+
+	fn consume( mut self: Pin<&mut Self>, amount: usize )
+	{
+		TokAsyncBufRead::consume( Pin::new( &mut self.inner ), amount )
+	}
+}
+
+
+
+impl<St, I> AsyncWrite for IoWriteHalf<St, I>
+where
+
+	St: Sink< I, Error=io::Error >,
+	I : From< Vec<u8> >,
+
+{
+	fn poll_write( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8] ) -> Poll< io::Result<usize> >
+	{
+		AsyncWrite::poll_write( Pin::new( &mut self.inner ), cx, buf )
+	}
+
+	fn poll_write_vectored( mut self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &[ IoSlice<'_> ] ) -> Poll< io::Result<usize> >
+	{
+		AsyncWrite::poll_write_vectored( Pin::new( &mut self.inner ), cx, bufs )
+	}
+
+	fn poll_flush( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		AsyncWrite::poll_flush( Pin::new( &mut self.inner ), cx )
+	}
+
+	fn poll_close( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		AsyncWrite::poll_close( Pin::new( &mut self.inner ), cx )
+	}
+}
+
+
+
+#[ cfg( feature = "tokio_io" ) ]
+//
+#[ cfg_attr( nightly, doc(cfg( feature = "tokio_io" )) ) ]
+//
+/// This impl requires the `tokio_io` feature.
+//
+impl<St, I> TokAsyncWrite for IoWriteHalf<St, I>
+where
+
+	St: Sink< I, Error=io::Error >,
+	I : From< Vec<u8> >,
+
+{
+	fn poll_write( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8] ) -> Poll< io::Result<usize> >
+	{
+		TokAsyncWrite::poll_write( Pin::new( &mut self.inner ), cx, buf )
+	}
+
+	fn poll_write_vectored( mut self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &[ IoSlice<'_> ] ) -> Poll< io::Result<usize> >
+	{
+		TokAsyncWrite::poll_write_vectored( Pin::new( &mut self.inner ), cx, bufs )
+	}
+
+	fn is_write_vectored( &self ) -> bool
+	{
+		TokAsyncWrite::is_write_vectored( &self.inner )
+	}
+
+	fn poll_flush( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		TokAsyncWrite::poll_flush( Pin::new( &mut self.inner ), cx )
+	}
+
+	fn poll_shutdown( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		TokAsyncWrite::poll_shutdown( Pin::new( &mut self.inner ), cx )
+	}
+}
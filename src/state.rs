@@ -0,0 +1,57 @@
+use
+{
+	crate :: { IoStream, ReadState } ,
+	std   :: { io                  } ,
+};
+
+
+
+impl<St, I> IoStream<St, I>
+where
+
+	St: Unpin,
+	I : AsRef<[u8]>,
+
+{
+	/// A snapshot of the read side state: how many bytes are buffered and whether an error or the
+	/// end of the stream will be returned by the next read.
+	//
+	pub fn state( &self ) -> IoState
+	{
+		let mut state = IoState::default();
+
+		for entry in &self.state
+		{
+			match entry
+			{
+				ReadState::Ready{ chunk } => state.buffered += chunk.get_ref().as_ref().len() - chunk.position() as usize,
+				ReadState::Error{ error } => state.error     = state.error.or( Some( error.kind() ) ),
+				ReadState::Eof            => state.eof       = true,
+			}
+		}
+
+		state
+	}
+}
+
+
+
+/// The read side state of an [`IoStream`], returned by [`IoStream::state`].
+//
+#[ derive( Debug, Clone, Copy, PartialEq, Eq, Default ) ]
+//
+pub struct IoState
+{
+	/// Bytes received from the stream that have not been returned to the reader yet.
+	//
+	pub buffered: usize,
+
+	/// The kind of an error from the stream that will be returned from a read, after the buffered
+	/// bytes in front of it. If several are buffered, this is the first one.
+	//
+	pub error: Option< io::ErrorKind >,
+
+	/// The stream has ended. Reads will return 0 once the buffered bytes have been read.
+	//
+	pub eof: bool,
+}
@@ -0,0 +1,115 @@
+use
+{
+	crate :: { IoStream } ,
+};
+
+
+
+impl<St, I> IoStream<St, I>
+where
+
+	St: Unpin,
+
+{
+	/// Counters of what went through this IoStream since it was created. Useful to monitor
+	/// throughput per connection.
+	//
+	pub fn stats( &self ) -> Stats
+	{
+		self.stats
+	}
+}
+
+
+
+/// Counters of what went through an [`IoStream`], returned by [`IoStream::stats`].
+///
+/// Items and bytes are counted as they are received from the stream and handed to the sink, not
+/// as they are read or written by the caller, so buffered data is counted once.
+//
+#[ derive( Debug, Clone, Copy, PartialEq, Eq, Default ) ]
+//
+pub struct Stats
+{
+	/// Items received from the stream.
+	//
+	pub items_read: u64,
+
+	/// Bytes in the items received from the stream.
+	//
+	pub bytes_read: u64,
+
+	/// The size in bytes of the biggest item received from the stream.
+	//
+	pub largest_item_read: usize,
+
+	/// Errors from the stream that were buffered to be returned by the next read, because the
+	/// current read already had data to return.
+	//
+	pub read_errors: u64,
+
+	/// Items sent to the sink.
+	//
+	pub items_written: u64,
+
+	/// Bytes in the items sent to the sink.
+	//
+	pub bytes_written: u64,
+
+	/// The size in bytes of the biggest item sent to the sink.
+	//
+	pub largest_item_written: usize,
+
+	/// Flushes of the sink that completed successfully, implicit ones included.
+	//
+	pub flushes: u64,
+
+	/// How many times the sink was not ready to take an item.
+	//
+	pub ready_pending: u64,
+
+	/// Errors from the sink that were buffered to be returned by the next write, because they
+	/// happened after the data of the current write was already taken.
+	//
+	pub write_errors: u64,
+}
+
+
+impl Stats
+{
+	// Count an item received from the stream.
+	//
+	pub(crate) fn read( &mut self, len: usize )
+	{
+		self.items_read       += 1;
+		self.bytes_read       += len as u64;
+		self.largest_item_read = self.largest_item_read.max( len );
+	}
+
+
+	// Count an item sent to the sink.
+	//
+	pub(crate) fn written( &mut self, len: usize )
+	{
+		self.items_written        += 1;
+		self.bytes_written        += len as u64;
+		self.largest_item_written  = self.largest_item_written.max( len );
+	}
+
+
+	// When reuniting split halves, take the read counters from the read half and the write counters
+	// from the write half.
+	//
+	pub(crate) fn join( read: Self, write: Self ) -> Self
+	{
+		Self
+		{
+			items_read       : read.items_read        ,
+			bytes_read       : read.bytes_read        ,
+			largest_item_read: read.largest_item_read ,
+			read_errors      : read.read_errors       ,
+
+			..write
+		}
+	}
+}
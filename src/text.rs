@@ -0,0 +1,518 @@
+use
+{
+	crate   :: { IoStream, FlushPolicy, Stats                                          } ,
+	std     :: { fmt, io::{ self, IoSlice, IoSliceMut }, str                             } ,
+	std     :: { pin::Pin, task::{ Poll, Context }                                       } ,
+	futures :: { TryStream, Stream, Sink, ready, AsyncRead, AsyncWrite, AsyncBufRead     } ,
+};
+
+
+#[ cfg( feature = "tokio_io" ) ]
+//
+use tokio::io::{ AsyncRead as TokAsyncRead, AsyncWrite as TokAsyncWrite, AsyncBufRead as TokAsyncBufRead };
+
+
+
+/// An [`IoStream`] for transports that only accept text, like websocket text frames. Only valid
+/// UTF-8 is sent, as items created from a `String`, and items are read as bytes through
+/// [`AsRef<str>`]. So `I` only needs `From<String>` for writing and `AsRef<str>` for reading.
+///
+/// Writes don't need to end on a character boundary, an incomplete code point at the end of a
+/// write is kept in the write buffer until the next write completes it. Writes that start with
+/// invalid UTF-8 fail with [`io::ErrorKind::InvalidData`], writes that contain it further on only
+/// accept the bytes before it.
+///
+/// Flushing sends everything except an incomplete code point. Closing while one is left fails
+/// with [`io::ErrorKind::InvalidData`] and drops it.
+///
+/// Implements [`AsyncRead`], [`AsyncBufRead`] and [`AsyncWrite`], as well as the tokio versions
+/// when the `tokio_io` feature is enabled.
+//
+pub struct TextStream<St, I>
+where
+
+	St: Unpin,
+
+{
+	inner: IoStream< Text<St>, TextItem<I> >,
+}
+
+
+
+impl<St, I> TextStream<St, I>
+where
+
+	St: Unpin,
+
+{
+	/// Create a new TextStream.
+	//
+	pub fn new( inner: St ) -> Self
+	{
+		let mut inner = IoStream::new( Text( inner ) );
+
+		inner.text = Some( TextItem::String );
+
+		Self{ inner }
+	}
+
+
+	/// See [`IoStream::with_write_buffer`]. Only whole code points are sent when the buffer is full.
+	//
+	pub fn with_write_buffer( mut self, capacity: usize ) -> Self
+	{
+		self.inner = self.inner.with_write_buffer( capacity );
+		self
+	}
+
+
+	/// See [`IoStream::with_flush_policy`].
+	//
+	pub fn with_flush_policy( mut self, policy: FlushPolicy ) -> Self
+	{
+		self.inner = self.inner.with_flush_policy( policy );
+		self
+	}
+
+
+	/// See [`IoStream::with_max_item_size`]. A code point that doesn't fit in an item starts the
+	/// next one.
+	///
+	/// # Panics
+	///
+	/// When `max` is smaller than 4, as then not every character fits in an item.
+	//
+	pub fn with_max_item_size( mut self, max: usize ) -> Self
+	{
+		self.inner = self.inner.with_max_item_size( max );
+		self
+	}
+
+
+	/// See [`IoStream::with_max_read_item_size`].
+	//
+	pub fn with_max_read_item_size( mut self, max: usize ) -> Self
+	{
+		self.inner = self.inner.with_max_read_item_size( max );
+		self
+	}
+
+
+	/// See [`IoStream::with_min_fill_buf`].
+	//
+	pub fn with_min_fill_buf( mut self, min: usize ) -> Self
+	{
+		self.inner = self.inner.with_min_fill_buf( min );
+		self
+	}
+
+
+	/// Get a reference to the inner stream.
+	//
+	pub fn inner( &self ) -> &St
+	{
+		&self.inner.inner().0
+	}
+
+
+	/// Get a mut reference to the inner stream.
+	//
+	pub fn inner_mut( &mut self ) -> &mut St
+	{
+		&mut self.inner.inner_mut().0
+	}
+
+
+	/// See [`IoStream::stats`].
+	//
+	pub fn stats( &self ) -> Stats
+	{
+		self.inner.stats()
+	}
+}
+
+
+
+impl<St, I> fmt::Debug for TextStream<St, I>
+where
+
+	St: Unpin,
+
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		f.debug_tuple( "TextStream" ).field( &self.inner ).finish()
+	}
+}
+
+
+
+// Creates an item from a String. It's set by TextStream::new, but it lives in IoStream<St, I>.
+//
+pub(crate) type TextFn<I> = fn(String) -> I;
+
+
+
+// Adapts the transport of a TextStream to the items of its IoStream.
+//
+pub(crate) struct Text<St>( St );
+
+
+// The items of the IoStream in a TextStream. Items from the stream are read through AsRef<str>. Text
+// mode creates items from a String, and reading creates items from bytes when it merges or splits
+// them. Only the first two can be sent.
+//
+pub(crate) enum TextItem<I>
+{
+	Item  ( I       ),
+	String( String  ),
+	Bytes ( Vec<u8> ),
+}
+
+
+impl<I> AsRef<[u8]> for TextItem<I>
+where
+
+	I: AsRef<str>,
+
+{
+	fn as_ref( &self ) -> &[u8]
+	{
+		match self
+		{
+			TextItem::Item  ( item  ) => item.as_ref().as_bytes(),
+			TextItem::String( text  ) => text.as_bytes()         ,
+			TextItem::Bytes ( bytes ) => bytes                   ,
+		}
+	}
+}
+
+
+impl<I> From< Vec<u8> > for TextItem<I>
+{
+	fn from( bytes: Vec<u8> ) -> Self
+	{
+		TextItem::Bytes( bytes )
+	}
+}
+
+
+impl<St> Stream for Text<St>
+where
+
+	St: TryStream + Unpin,
+
+{
+	type Item = Result< TextItem<St::Ok>, St::Error >;
+
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Option<Self::Item> >
+	{
+		let item = ready!( Pin::new( &mut self.0 ).try_poll_next( cx ) );
+
+		Poll::Ready( item.map( |item| item.map( TextItem::Item ) ) )
+	}
+}
+
+
+impl<St, I> Sink< TextItem<I> > for Text<St>
+where
+
+	St: Sink< I, Error=io::Error > + Unpin,
+	I : From<String>,
+
+{
+	type Error = io::Error;
+
+	fn poll_ready( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		Pin::new( &mut self.0 ).poll_ready( cx )
+	}
+
+
+	fn start_send( mut self: Pin<&mut Self>, item: TextItem<I> ) -> io::Result<()>
+	{
+		let item = match item
+		{
+			TextItem::Item  ( item ) => item,
+			TextItem::String( text ) => I::from( text ),
+
+			TextItem::Bytes(_) =>
+			{
+				return Err( io::Error::new( io::ErrorKind::InvalidData, "TextStream: can only send text" ) );
+			}
+		};
+
+		Pin::new( &mut self.0 ).start_send( item )
+	}
+
+
+	fn poll_flush( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		Pin::new( &mut self.0 ).poll_flush( cx )
+	}
+
+
+	fn poll_close( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		Pin::new( &mut self.0 ).poll_close( cx )
+	}
+}
+
+
+
+impl<St, I> AsyncRead for TextStream<St, I>
+where
+
+	St: TryStream< Ok=I, Error=io::Error > + Unpin,
+	I : AsRef<str>,
+
+{
+	fn poll_read( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8] ) -> Poll< io::Result<usize> >
+	{
+		AsyncRead::poll_read( Pin::new( &mut self.inner ), cx, buf )
+	}
+
+	fn poll_read_vectored( mut self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &mut [IoSliceMut<'_>] ) -> Poll< io::Result<usize> >
+	{
+		AsyncRead::poll_read_vectored( Pin::new( &mut self.inner ), cx, bufs )
+	}
+}
+
+
+
+impl<St, I> AsyncBufRead for TextStream<St, I>
+where
+
+	St: TryStream< Ok=I, Error=io::Error > + Unpin,
+	I : AsRef<str> + Unpin,
+
+{
+	fn poll_fill_buf( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<&[u8]> >
+	{
+		AsyncBufRead::poll_fill_buf( Pin::new( &mut self.get_mut().inner ), cx )
+	}
+
+	fn consume( mut self: Pin<&mut Self>, amount: usize )
+	{
+		AsyncBufRead::consume( Pin::new( &mut self.inner ), amount )
+	}
+}
+
+
+
+#[ cfg( feature = "tokio_io" ) ]
+//
+#[ cfg_attr( nightly, doc(cfg( feature = "tokio_io" )) ) ]
+//
+/// This impl requires the `tokio_io` feature.
+//
+impl<St, I> TokAsyncRead for TextStream<St, I>
+where
+
+	St: TryStream< Ok=I, Error=io::Error > + Unpin,
+	I : AsRef<str>,
+
+{
+	fn poll_read( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut tokio::io::ReadBuf<'_> ) -> Poll< io::Result<()> >
+	{
+		TokAsyncRead::poll_read( Pin::new( &mut self.inner ), cx, buf )
+	}
+}
+
+
+
+#[ cfg( feature = "tokio_io" ) ]
+//
+#[ cfg_attr( nightly, doc(cfg( feature = "tokio_io" )) ) ]
+//
+/// This impl requires the `tokio_io` feature.
+//
+impl<St, I> TokAsyncBufRead for TextStream<St, I>
+where
+
+	St: TryStream< Ok=I, Error=io::Error > + Unpin,
+	I : AsRef<str>,
+
+{
+	fn poll_fill_buf( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<&[u8]> >
+	{
+		TokAsyncBufRead::poll_fill_buf( Pin::new( &mut self.get_mut().inner ), cx )
+	}
+
+	fn consume( mut self: Pin<&mut Self>, amount: usize )
+	{
+		TokAsyncBufRead::consume( Pin::new( &mut self.inner ), amount )
+	}
+}
+
+
+
+impl<St, I> AsyncWrite for TextStream<St, I>
+where
+
+	St: Sink< I, Error=io::Error > + Unpin,
+	I : From<String>,
+
+{
+	fn poll_write( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8] ) -> Poll< io::Result<usize> >
+	{
+		AsyncWrite::poll_write( Pin::new( &mut self.inner ), cx, buf )
+	}
+
+	fn poll_write_vectored( mut self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &[ IoSlice<'_> ] ) -> Poll< io::Result<usize> >
+	{
+		AsyncWrite::poll_write_vectored( Pin::new( &mut self.inner ), cx, bufs )
+	}
+
+	fn poll_flush( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		AsyncWrite::poll_flush( Pin::new( &mut self.inner ), cx )
+	}
+
+	fn poll_close( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		AsyncWrite::poll_close( Pin::new( &mut self.inner ), cx )
+	}
+}
+
+
+
+#[ cfg( feature = "tokio_io" ) ]
+//
+#[ cfg_attr( nightly, doc(cfg( feature = "tokio_io" )) ) ]
+//
+/// This impl requires the `tokio_io` feature.
+//
+impl<St, I> TokAsyncWrite for TextStream<St, I>
+where
+
+	St: Sink< I, Error=io::Error > + Unpin,
+	I : From<String>,
+
+{
+	fn poll_write( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8] ) -> Poll< io::Result<usize> >
+	{
+		TokAsyncWrite::poll_write( Pin::new( &mut self.inner ), cx, buf )
+	}
+
+	fn poll_write_vectored( mut self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &[ IoSlice<'_> ] ) -> Poll< io::Result<usize> >
+	{
+		TokAsyncWrite::poll_write_vectored( Pin::new( &mut self.inner ), cx, bufs )
+	}
+
+	fn is_write_vectored( &self ) -> bool
+	{
+		TokAsyncWrite::is_write_vectored( &self.inner )
+	}
+
+	fn poll_flush( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		TokAsyncWrite::poll_flush( Pin::new( &mut self.inner ), cx )
+	}
+
+	fn poll_shutdown( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		TokAsyncWrite::poll_shutdown( Pin::new( &mut self.inner ), cx )
+	}
+}
+
+
+
+// How many bytes of `bufs`, up to `limit`, can be accepted as UTF-8, given that they follow the
+// incomplete code point `tail`. This stops before the first invalid byte. An incomplete code point
+// at the end is accepted, since the next write might complete it.
+//
+pub(crate) fn valid_len( tail: &[u8], bufs: &[ IoSlice<'_> ], limit: usize ) -> usize
+{
+	let mut pending = tail.to_vec();
+	let mut valid   = 0;
+
+	for buf in bufs
+	{
+		let mut buf = &buf[ ..buf.len().min( limit - valid ) ];
+
+		// Complete a code point from the previous buffer one byte at a time.
+		//
+		while !pending.is_empty() && !buf.is_empty()
+		{
+			pending.push( buf[0] );
+
+			match str::from_utf8( &pending )
+			{
+				Ok (_)                           => pending.clear() ,
+				Err(e) if e.error_len().is_none() => {}              ,
+				Err(_)                           => return valid    ,
+			}
+
+			valid += 1;
+			buf    = &buf[1..];
+		}
+
+		match str::from_utf8( buf )
+		{
+			Ok(_) => valid += buf.len(),
+
+			Err(e) =>
+			{
+				valid += e.valid_up_to();
+
+				if e.error_len().is_some()
+				{
+					return valid;
+				}
+
+				pending.extend_from_slice( &buf[ e.valid_up_to().. ] );
+				valid += buf.len() - e.valid_up_to();
+			}
+		}
+
+		if valid == limit { break }
+	}
+
+	valid
+}
+
+
+
+// The length of an incomplete code point at the end of `bytes`, which must be valid UTF-8 otherwise.
+//
+pub(crate) fn incomplete_tail( bytes: &[u8] ) -> usize
+{
+	for back in 1..=bytes.len().min( 3 )
+	{
+		let byte = bytes[ bytes.len() - back ];
+
+		// Skip continuation bytes until we find the start of the last code point.
+		//
+		if byte & 0b1100_0000 == 0b1000_0000 { continue }
+
+		let width = match byte
+		{
+			0x00..=0x7F => 1,
+			0xC0..=0xDF => 2,
+			0xE0..=0xEF => 3,
+			_           => 4,
+		};
+
+		return if width > back { back } else { 0 };
+	}
+
+	0
+}
+
+
+
+// The biggest number of bytes, up to `max`, that can be taken from the start of `bytes` without
+// splitting a code point. `bytes` must be valid UTF-8.
+//
+pub(crate) fn char_boundary( bytes: &[u8], max: usize ) -> usize
+{
+	let mut take = max.min( bytes.len() );
+
+	while take < bytes.len() && bytes[take] & 0b1100_0000 == 0b1000_0000
+	{
+		take -= 1;
+	}
+
+	take
+}
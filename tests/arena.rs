@@ -0,0 +1,110 @@
+#![ cfg( feature = "bytes" ) ]
+
+// Test IoStream::with_arena:
+//
+// ✔ items contain the written data
+// ✔ the memory of dropped items is reused
+// ✔ works together with the write buffer and the maximum item size
+// ✔ the rest of a partially read item is a slice of the original
+//
+use
+{
+	async_io_stream   :: { *                 } ,
+	futures           :: { *, channel::mpsc  } ,
+	std               :: { io                } ,
+	bytes             :: { Bytes             } ,
+	pretty_assertions :: { assert_eq         } ,
+};
+
+
+type Transport = Joined
+<
+	stream::Map< mpsc::Receiver<Bytes>, fn(Bytes) -> io::Result<Bytes> >,
+	sink::SinkMapErr< mpsc::Sender<Bytes>, fn(mpsc::SendError) -> io::Error >,
+>;
+
+
+// Returns the IoStream, a sender to feed it items to read and a receiver for the items written.
+//
+fn arena( capacity: usize ) -> ( IoStream<Transport, Bytes>, mpsc::Sender<Bytes>, mpsc::Receiver<Bytes> )
+{
+	let (out_tx, out_rx) = mpsc::channel( 8 );
+	let (in_tx , in_rx ) = mpsc::channel( 8 );
+
+	let stream = in_rx.map( Ok as fn(Bytes) -> io::Result<Bytes> );
+	let sink   = out_tx.sink_map_err( (|_| io::ErrorKind::NotConnected.into()) as fn(mpsc::SendError) -> io::Error );
+
+	( IoStream::join( stream, sink ).with_arena( capacity ), in_tx, out_rx )
+}
+
+
+
+// The data arrives and the allocation is reused once the item is dropped.
+//
+#[ test ] fn reuse()
+{
+	let (mut wrap, _tx, mut rx) = arena( 4 );
+
+	executor::block_on( async
+	{
+		wrap.write_all( &[ 1, 2, 3 ] ).await.expect( "write" );
+
+		let first = rx.next().await.expect( "item" );
+		let ptr   = first.as_ptr();
+
+		assert_eq!( &first[..], &[ 1, 2, 3 ] );
+		drop( first );
+
+		// There is only room for one more byte, so this has to reclaim the memory of the first item.
+		//
+		wrap.write_all( &[ 4, 5, 6 ] ).await.expect( "write" );
+
+		let second = rx.next().await.expect( "item" );
+
+		assert_eq!( &second[..], &[ 4, 5, 6 ] );
+		assert_eq!( second.as_ptr(), ptr );
+	});
+}
+
+
+// Buffered and size limited writes.
+//
+#[ test ] fn buffered()
+{
+	let (wrap, _tx, mut rx) = arena( 64 );
+	let mut wrap = wrap.with_write_buffer( 4 ).with_max_item_size( 3 );
+
+	executor::block_on( async
+	{
+		wrap.write_all( &[ 1 ] ).await.expect( "write" );
+		wrap.write_all( &[ 2, 3, 4, 5 ] ).await.expect( "write" );
+		wrap.flush().await.expect( "flush" );
+
+		assert_eq!( &rx.next().await.expect( "item" )[..], &[ 1, 2, 3 ] );
+		assert_eq!( &rx.next().await.expect( "item" )[..], &[ 4, 5    ] );
+	});
+}
+
+
+// poll_next_item slices the original item.
+//
+#[ test ] fn tail()
+{
+	let (mut wrap, mut tx, _rx) = arena( 0 );
+
+	executor::block_on( async
+	{
+		let item = Bytes::from( vec![ 1, 2, 3, 4 ] );
+		let ptr  = item.as_ptr();
+
+		tx.send( item ).await.expect( "send" );
+
+		let mut buf = [ 0u8; 1 ];
+		wrap.read_exact( &mut buf ).await.expect( "read" );
+
+		let tail = wrap.next_item().await.expect( "item" ).expect( "no error" );
+
+		assert_eq!( &tail[..]    , &[ 2, 3, 4 ]         );
+		assert_eq!( tail.as_ptr(), ptr.wrapping_add( 1 ) );
+	});
+}
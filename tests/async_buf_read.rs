@@ -6,12 +6,16 @@
 //   ✔ at the end of the stream an empty buffer is returned.
 //   ✔ errors must be returned
 //   ✔ pending must be returned
+//   ✔ empty items are skipped
 //
 // - create different situations of consume:
 //   ✔ consume 0
 //   ✔ consume part
 //   ✔ consume an entire message at once
-//   ✔ more than the buffer size
+//   ✔ more than the buffer size only consumes the buffer
+//   ✔ consume without a buffer does nothing
+//
+// - all of the above for the tokio impl when the tokio_io feature is enabled.
 //
 mod common;
 
@@ -28,7 +32,7 @@ use
 
 
 
-#[ derive( Debug, PartialEq, Eq ) ]
+#[ derive( Debug, PartialEq, Eq, Clone ) ]
 //
 enum Output
 {
@@ -39,6 +43,16 @@ enum Output
 
 
 fn tester( actions: Vec<Action>, expect: Vec<Output>, consume: Vec<usize>, polled: usize )
+{
+	tester_futures( actions.clone(), expect.clone(), consume.clone(), polled );
+
+	#[ cfg( feature = "tokio_io" ) ]
+	//
+	tester_tokio( actions, expect, consume, polled );
+}
+
+
+fn tester_futures( actions: Vec<Action>, expect: Vec<Output>, consume: Vec<usize>, polled: usize )
 {
 	let stream = TestStream::new( actions.into() );
 
@@ -63,6 +77,33 @@ fn tester( actions: Vec<Action>, expect: Vec<Output>, consume: Vec<usize>, polle
 }
 
 
+#[ cfg( feature = "tokio_io" ) ]
+//
+fn tester_tokio( actions: Vec<Action>, expect: Vec<Output>, consume: Vec<usize>, polled: usize )
+{
+	let stream = TestStream::new( actions.into() );
+
+	let mut wrapped = IoStream::new( stream );
+	let     waker   = noop_waker();
+	let mut cx      = Context::from_waker( &waker );
+
+	for (i, out) in expect.iter().enumerate()
+	{
+		match tokio::io::AsyncBufRead::poll_fill_buf( Pin::new( &mut wrapped ), &mut cx )
+		{
+			Poll::Ready(Ok (data)) => assert_matches!( out, Output::Data ( exp ) => assert_eq!( data     , &exp[..] ) ),
+			Poll::Ready(Err(e   )) => assert_matches!( out, Output::Error( err ) => assert_eq!( &e.kind(), err      ) ),
+			Poll::Pending          => assert_eq!     ( out, &Output::Pending                                          ),
+		}
+
+		debug!( "consume: {}", consume[i] );
+		tokio::io::AsyncBufRead::consume( Pin::new( &mut wrapped ), consume[i] );
+	}
+
+	assert_eq!( wrapped.inner().polled(), polled );
+}
+
+
 // Send in an empty buffer.
 //
 #[ test ] fn consume_zero()
@@ -105,9 +146,9 @@ fn tester( actions: Vec<Action>, expect: Vec<Output>, consume: Vec<usize>, polle
 }
 
 
-// Send in an empty buffer.
+// Consuming more than the buffer only consumes the buffer.
 //
-#[ test ] #[ should_panic ] fn over_consume()
+#[ test ] fn over_consume()
 {
 	// flexi_logger::Logger::with_str( "trace" ).start().expect( "flexi_logger");
 
@@ -146,3 +187,28 @@ fn tester( actions: Vec<Action>, expect: Vec<Output>, consume: Vec<usize>, polle
 	tester( actions, expect, consume, 3 );
 }
 
+
+
+// Consume before anything was buffered, and after the end of the stream.
+//
+#[ test ] fn consume_without_buffer()
+{
+	let actions = vec![ Action::Pending, vec![ 1, 2, 3 ].into() ];
+	let consume = vec![ 3, 3, 5, 0 ];
+	let expect  = vec![ Output::Pending, Output::Data(vec![ 1, 2, 3 ]), Output::Data(vec![]), Output::Data(vec![]) ];
+
+	tester( actions, expect, consume, 3 );
+}
+
+
+
+// Empty items don't look like the end of the stream.
+//
+#[ test ] fn empty_items()
+{
+	let actions = vec![ vec![].into(), vec![ 1 ].into(), vec![].into(), vec![].into(), Action::Pending, vec![].into(), vec![ 2 ].into() ];
+	let consume = vec![ 1, 0, 1, 0 ];
+	let expect  = vec![ Output::Data(vec![ 1 ]), Output::Pending, Output::Data(vec![ 2 ]), Output::Data(vec![]) ];
+
+	tester( actions, expect, consume, 8 );
+}
@@ -20,6 +20,8 @@
 //    - if second poll returns pending, waker shouldn't be woken up
 //    ✔ what if second poll returns error
 //
+// ✔ tokio: only the bytes read are initialized in an uninitialized ReadBuf
+//
 mod common;
 
 use
@@ -283,3 +285,27 @@ fn tester_tokio( actions: Vec<Action>, read_out: Vec<Output>, expect: Vec<Vec<u8
 
 	tester( actions, read_out, expect, 2 );
 }
+
+
+// Tokio: the unfilled part of the ReadBuf is not initialized by reading, and data of several
+// items is still combined.
+//
+#[ cfg( feature = "tokio_io" ) ]
+//
+#[ test ] fn tokio_uninit()
+{
+	let actions = vec![ vec![ 1, 2 ].into(), vec![ 3 ].into(), Action::Pending ];
+
+	let mut wrapped = IoStream::new( TestStream::new( actions.into() ) );
+	let     waker   = noop_waker();
+	let mut cx      = Context::from_waker( &waker );
+	let mut buf     = [ std::mem::MaybeUninit::<u8>::uninit(); 8 ];
+	let mut readbuf = tokio::io::ReadBuf::uninit( &mut buf );
+
+	let result = tokio::io::AsyncRead::poll_read( Pin::new( &mut wrapped ), &mut cx, &mut readbuf );
+
+	assert_matches!( result, Poll::Ready(Ok(())) );
+
+	assert_eq!( readbuf.filled()     , &[ 1, 2, 3 ] );
+	assert_eq!( readbuf.initialized(), &[ 1, 2, 3 ] );
+}
@@ -3,9 +3,10 @@
 
 use
 {
-	futures :: { * } ,
-	log     :: { * } ,
-	std     :: { io, task::{ Poll, Context }, pin::Pin, collections::VecDeque } ,
+	async_io_stream :: { IoStream            } ,
+	futures         :: { *, task::noop_waker } ,
+	log             :: { *                   } ,
+	std             :: { io, task::{ Poll, Context, Waker }, pin::Pin, collections::VecDeque } ,
 };
 
 
@@ -134,6 +135,8 @@ pub struct TestSink
 	pub flush_actions : Vec< FlushAction > ,
 
 	pub items: Vec< Vec<u8> > ,
+
+	pub flush_waker: Option<Waker> , // The waker from the last call to poll_flush.
 }
 
 
@@ -150,9 +153,19 @@ impl TestSink
 			send_actions  ,
 			flush_actions ,
 
-			items: Vec::new() ,
+			items      : Vec::new() ,
+			flush_waker: None       ,
 		}
 	}
+
+
+	// A sink that accepts `n` items. There are two flush actions per item, as every item can be
+	// flushed implicitly and by an explicit flush.
+	//
+	pub fn ok( n: usize ) -> Self
+	{
+		Self::new( vec![ ReadyAction::Ok; n ], vec![ SendAction::Ok; n ], vec![ FlushAction::Ok; n * 2 ] )
+	}
 }
 
 
@@ -191,9 +204,10 @@ impl Sink< Vec<u8> > for TestSink
 	}
 
 
-	fn poll_flush( mut self: Pin<&mut Self>, _cx: &mut Context ) -> Poll<Result<(), Self::Error>>
+	fn poll_flush( mut self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
 	{
 		self.poll_flush += 1;
+		self.flush_waker = Some( cx.waker().clone() );
 
 		match self.flush_actions[ self.poll_flush - 1 ]
 		{
@@ -209,3 +223,99 @@ impl Sink< Vec<u8> > for TestSink
 		Poll::Ready(Ok(()))
 	}
 }
+
+
+
+// Combines a TestStream and a TestSink in one object, like a network transport.
+//
+pub struct TestTransport
+{
+	pub stream: TestStream ,
+	pub sink  : TestSink   ,
+}
+
+
+impl TestTransport
+{
+	pub fn new( stream: TestStream, sink: TestSink ) -> Self
+	{
+		Self { stream, sink }
+	}
+}
+
+
+// An IoStream over a TestTransport that reads the given actions. Every send succeeds and
+// there is a poll_ready and a start_send for each flush action.
+//
+pub fn transport( actions: Vec<Action>, fa: Vec<FlushAction> ) -> IoStream<TestTransport, Vec<u8>>
+{
+	let ra = vec![ ReadyAction::Ok; fa.len() ];
+	let sa = vec![ SendAction::Ok ; fa.len() ];
+
+	IoStream::new( TestTransport::new( TestStream::new( actions.into() ), TestSink::new( ra, sa, fa ) ) )
+}
+
+
+impl Stream for TestTransport
+{
+	type Item = Result< Vec<u8>, io::Error >;
+
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Option<Self::Item>>
+	{
+		Pin::new( &mut self.stream ).poll_next( cx )
+	}
+}
+
+
+impl Sink< Vec<u8> > for TestTransport
+{
+	type Error = io::Error;
+
+	fn poll_ready( mut self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		Pin::new( &mut self.sink ).poll_ready( cx )
+	}
+
+
+	fn start_send( mut self: Pin<&mut Self>, item: Vec<u8> ) -> Result<(), Self::Error>
+	{
+		Pin::new( &mut self.sink ).start_send( item )
+	}
+
+
+	fn poll_flush( mut self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		Pin::new( &mut self.sink ).poll_flush( cx )
+	}
+
+
+	fn poll_close( mut self: Pin<&mut Self>, cx: &mut Context ) -> Poll<Result<(), Self::Error>>
+	{
+		Pin::new( &mut self.sink ).poll_close( cx )
+	}
+}
+
+
+
+// Read up to `len` bytes with a noop waker and return them.
+//
+pub fn read( wrap: &mut IoStream<TestStream, Vec<u8>>, len: usize ) -> Poll< io::Result<Vec<u8>> >
+{
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+	let mut buf   = vec![ 0u8; len ];
+
+	Pin::new( wrap ).poll_read( &mut cx, &mut buf ).map_ok( |n| buf[..n].to_vec() )
+}
+
+
+
+// Write `data` with a noop waker.
+//
+pub fn write<W: AsyncWrite + Unpin>( wrap: &mut W, data: &[u8] ) -> Poll< io::Result<usize> >
+{
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	Pin::new( wrap ).poll_write( &mut cx, data )
+}
@@ -0,0 +1,114 @@
+#![ cfg( feature = "map_pharos" ) ]
+
+// Test IoStream::observe_io:
+//
+// ✔ items, a buffered read error and the end of the stream
+// ✔ a split write, a coalesced write and a deferred write error
+// ✔ observers see the events of both halves of a split IoStream
+//
+mod common;
+
+use
+{
+	common            :: { *                                 } ,
+	async_io_stream   :: { *                                 } ,
+	futures           :: { *, task::noop_waker               } ,
+	std               :: { task::{ Poll, Context }, pin::Pin } ,
+	pharos            :: { ObserveConfig                     } ,
+	pretty_assertions :: { assert_eq                         } ,
+	assert_matches    :: { assert_matches                    } ,
+};
+
+
+
+// The events are sent on unbounded channels, so they are all there once the IoStream is dropped.
+//
+fn collect( events: pharos::Events<IoEvent> ) -> Vec<IoEvent>
+{
+	executor::block_on( events.collect() )
+}
+
+
+
+#[ test ] fn read()
+{
+	let actions = vec![ vec![ 1, 2 ].into(), Action::Error( io::ErrorKind::ConnectionReset ), vec![ 3 ].into() ];
+
+	let mut wrap   = IoStream::new( TestStream::new( actions.into() ) );
+	let     events = wrap.observe_io( ObserveConfig::default() ).expect( "observe" );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+	let mut buf   = [ 0u8; 4 ];
+
+	assert_matches!( Pin::new( &mut wrap ).poll_read( &mut cx, &mut buf ), Poll::Ready(Ok(2)) );
+	assert_matches!( Pin::new( &mut wrap ).poll_read( &mut cx, &mut buf ), Poll::Ready(Err(_)) );
+	assert_matches!( Pin::new( &mut wrap ).poll_read( &mut cx, &mut buf ), Poll::Ready(Ok(1)) );
+
+	drop( wrap );
+
+	assert_eq!( collect( events ), vec!
+	[
+		IoEvent::ItemReceived     { len : 2                              },
+		IoEvent::ReadErrorBuffered{ kind: io::ErrorKind::ConnectionReset },
+		IoEvent::ItemReceived     { len : 1                              },
+		IoEvent::Eof                                                      ,
+	]);
+}
+
+
+
+#[ test ] fn write()
+{
+	let ra = vec![ ReadyAction::Ok; 2 ];
+	let sa = vec![ SendAction::Ok ; 2 ];
+	let fa = vec![ FlushAction::Ok, FlushAction::Error( io::ErrorKind::NotConnected ) ];
+
+	let mut wrap   = IoStream::new( TestSink::new( ra, sa, fa ) ).with_max_item_size( 3 ).with_write_buffer( 2 );
+	let     events = wrap.observe_io( ObserveConfig::default() ).expect( "observe" );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_write( &mut cx, &[ 1, 2, 3, 4 ] ), Poll::Ready(Ok(3)) );
+	assert_matches!( Pin::new( &mut wrap ).poll_write( &mut cx, &[ 4          ] ), Poll::Ready(Ok(1)) );
+	assert_matches!( Pin::new( &mut wrap ).poll_write( &mut cx, &[ 5          ] ), Poll::Ready(Ok(1)) );
+
+	drop( wrap );
+
+	assert_eq!( collect( events ), vec!
+	[
+		IoEvent::WriteSplit        { taken: 3, len: 4                   },
+		IoEvent::WriteCoalesced    { len  : 2                           },
+		IoEvent::WriteErrorDeferred{ kind : io::ErrorKind::NotConnected },
+	]);
+}
+
+
+
+#[ test ] fn split()
+{
+	let ra = vec![ ReadyAction::Ok ];
+	let sa = vec![ SendAction::Ok  ];
+	let fa = vec![ FlushAction::Ok ];
+
+	let mut wrap   = IoStream::new( TestTransport::new( TestStream::new( vec![ vec![ 1 ].into() ].into() ), TestSink::new( ra, sa, fa ) ) ).with_max_item_size( 1 );
+	let     events = wrap.observe_io( ObserveConfig::default() ).expect( "observe" );
+
+	let (mut reader, mut writer) = wrap.split();
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+	let mut buf   = [ 0u8; 1 ];
+
+	assert_matches!( Pin::new( &mut reader ).poll_read ( &mut cx, &mut buf  ), Poll::Ready(Ok(1)) );
+	assert_matches!( Pin::new( &mut writer ).poll_write( &mut cx, &[ 1, 2 ] ), Poll::Ready(Ok(1)) );
+
+	drop( reader.reunite( writer ).expect( "reunite" ) );
+
+	assert_eq!( collect( events ), vec!
+	[
+		IoEvent::ItemReceived{ len  : 1         },
+		IoEvent::WriteSplit  { taken: 1, len: 2 },
+	]);
+}
@@ -0,0 +1,120 @@
+// Test IoStream::poll_fill_bufs and IoStream::consume_bufs:
+//
+// ✔ all items that are ready are returned, without waiting for more
+// ✔ no more items are taken than there are buffers
+// ✔ consume_bufs across items
+// ✔ an error or the end of the stream after the items is returned by the next call
+// ✔ pending when nothing is buffered
+//
+mod common;
+
+use
+{
+	common            :: { *                                              } ,
+	async_io_stream   :: { *                                              } ,
+	futures           :: { *, task::noop_waker                            } ,
+	std               :: { task::{ Poll, Context }, pin::Pin, io::IoSlice } ,
+	pretty_assertions :: { assert_eq                                      } ,
+	assert_matches    :: { assert_matches                                 } ,
+};
+
+
+
+// Returns the content of the filled buffers.
+//
+fn fill( wrap: &mut IoStream<TestStream, Vec<u8>>, slots: usize ) -> Poll< io::Result< Vec<Vec<u8>> > >
+{
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+	let mut bufs  = vec![ IoSlice::new( &[] ); slots ];
+
+	Pin::new( wrap ).poll_fill_bufs( &mut cx, &mut bufs )
+
+		.map_ok( |n| bufs[..n].iter().map( |b| b.to_vec() ).collect() )
+}
+
+
+
+#[ test ] fn ready_items()
+{
+	let actions = vec![ vec![ 1, 2 ].into(), vec![ 3 ].into(), Action::Pending, vec![ 4 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) );
+
+	assert_matches!( fill( &mut wrap, 4 ), Poll::Ready(Ok(bufs)) => assert_eq!( bufs, vec![ vec![ 1, 2 ], vec![ 3 ] ] ) );
+
+	assert_eq!( Pin::new( &mut wrap ).consume_bufs( 3 ), 0 );
+
+	assert_matches!( fill( &mut wrap, 4 ), Poll::Ready(Ok(bufs)) => assert_eq!( bufs, vec![ vec![ 4 ] ] ) );
+
+	assert_eq!( Pin::new( &mut wrap ).consume_bufs( 1 ), 0 );
+
+	assert_matches!( fill( &mut wrap, 4 ), Poll::Ready(Ok(bufs)) => assert!( bufs.is_empty() ) );
+}
+
+
+
+#[ test ] fn slots()
+{
+	let actions = vec![ vec![ 1 ].into(), vec![ 2 ].into(), vec![ 3 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) );
+
+	assert_matches!( fill( &mut wrap, 2 ), Poll::Ready(Ok(bufs)) => assert_eq!( bufs, vec![ vec![ 1 ], vec![ 2 ] ] ) );
+
+	assert_eq!( wrap.inner().polled(), 2 );
+}
+
+
+
+#[ test ] fn consume_across()
+{
+	let actions = vec![ vec![ 1, 2 ].into(), vec![ 3, 4 ].into(), vec![ 5 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) );
+
+	assert_matches!( fill( &mut wrap, 3 ), Poll::Ready(Ok(bufs)) => assert_eq!( bufs.len(), 3 ) );
+
+	assert_eq!( Pin::new( &mut wrap ).consume_bufs( 3 ), 2 );
+
+	assert_matches!( fill( &mut wrap, 3 ), Poll::Ready(Ok(bufs)) => assert_eq!( bufs, vec![ vec![ 4 ], vec![ 5 ] ] ) );
+
+	// More than is buffered.
+	//
+	assert_eq!( Pin::new( &mut wrap ).consume_bufs( 10 ), 0 );
+
+	assert_matches!( fill( &mut wrap, 3 ), Poll::Ready(Ok(bufs)) => assert!( bufs.is_empty() ) );
+}
+
+
+
+#[ test ] fn error()
+{
+	let actions = vec![ vec![ 1 ].into(), Action::Error( io::ErrorKind::ConnectionReset ), vec![ 2 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) );
+
+	assert_matches!( fill( &mut wrap, 3 ), Poll::Ready(Ok(bufs)) => assert_eq!( bufs, vec![ vec![ 1 ] ] ) );
+
+	assert_eq!( Pin::new( &mut wrap ).consume_bufs( 1 ), 0 );
+
+	assert_matches!
+	(
+		fill( &mut wrap, 3 ),
+		Poll::Ready(Err(e)) => assert_eq!( e.kind(), io::ErrorKind::ConnectionReset )
+	);
+
+	assert_matches!( fill( &mut wrap, 3 ), Poll::Ready(Ok(bufs)) => assert_eq!( bufs, vec![ vec![ 2 ] ] ) );
+}
+
+
+
+#[ test ] fn pending()
+{
+	let actions = vec![ Action::Pending, vec![ 1 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) );
+
+	assert_matches!( fill( &mut wrap, 3 ), Poll::Pending );
+	assert_matches!( fill( &mut wrap, 3 ), Poll::Ready(Ok(bufs)) => assert_eq!( bufs, vec![ vec![ 1 ] ] ) );
+}
@@ -0,0 +1,126 @@
+// Test IoStream::with_flush_policy:
+//
+// ✔ the default flushes after every item
+// ✔ Never only flushes on poll_flush
+// ✔ Items flushes every n items
+// ✔ Bytes flushes once n bytes were sent
+// ✔ an explicit flush resets the count
+//
+mod common;
+
+use
+{
+	common            :: { *                                 } ,
+	async_io_stream   :: { *                                 } ,
+	futures           :: { *, task::noop_waker               } ,
+	std               :: { task::{ Poll, Context }, pin::Pin } ,
+	pretty_assertions :: { assert_eq                         } ,
+	assert_matches    :: { assert_matches                    } ,
+};
+
+
+fn wrap( policy: FlushPolicy, writes: usize ) -> IoStream<TestSink, Vec<u8>>
+{
+	let ra = vec![ ReadyAction::Ok ; writes ];
+	let sa = vec![ SendAction::Ok  ; writes ];
+	let fa = vec![ FlushAction::Ok ; writes + 1 ];
+
+	IoStream::new( TestSink::new( ra, sa, fa ) ).with_flush_policy( policy )
+}
+
+
+fn flush( wrap: &mut IoStream<TestSink, Vec<u8>> ) -> Poll<io::Result<()>>
+{
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	Pin::new( wrap ).poll_flush( &mut cx )
+}
+
+
+
+// Every write is flushed by default.
+//
+#[ test ] fn always()
+{
+	let mut wrap = wrap( FlushPolicy::default(), 2 );
+
+	assert_matches!( write( &mut wrap, &[ 1 ] ), Poll::Ready(Ok(1)) );
+	assert_matches!( write( &mut wrap, &[ 2 ] ), Poll::Ready(Ok(1)) );
+
+	assert_eq!( wrap.inner().poll_flush, 2 );
+}
+
+
+// Only flush when asked.
+//
+#[ test ] fn never()
+{
+	let mut wrap = wrap( FlushPolicy::Never, 3 );
+
+	assert_matches!( write( &mut wrap, &[ 1 ] ), Poll::Ready(Ok(1)) );
+	assert_matches!( write( &mut wrap, &[ 2 ] ), Poll::Ready(Ok(1)) );
+	assert_matches!( write( &mut wrap, &[ 3 ] ), Poll::Ready(Ok(1)) );
+
+	assert_eq!( wrap.inner().poll_flush, 0 );
+
+	assert_matches!( flush( &mut wrap ), Poll::Ready(Ok(())) );
+
+	assert_eq!( wrap.inner().poll_flush, 1                                   );
+	assert_eq!( wrap.inner().items     , vec![ vec![ 1 ], vec![ 2 ], vec![ 3 ] ] );
+}
+
+
+// Flush after every second item.
+//
+#[ test ] fn items()
+{
+	let mut wrap = wrap( FlushPolicy::Items(2), 4 );
+
+	assert_matches!( write( &mut wrap, &[ 1 ] ), Poll::Ready(Ok(1)) );
+	assert_eq!( wrap.inner().poll_flush, 0 );
+
+	assert_matches!( write( &mut wrap, &[ 2 ] ), Poll::Ready(Ok(1)) );
+	assert_eq!( wrap.inner().poll_flush, 1 );
+
+	assert_matches!( write( &mut wrap, &[ 3 ] ), Poll::Ready(Ok(1)) );
+	assert_eq!( wrap.inner().poll_flush, 1 );
+
+	assert_matches!( write( &mut wrap, &[ 4 ] ), Poll::Ready(Ok(1)) );
+	assert_eq!( wrap.inner().poll_flush, 2 );
+}
+
+
+// Flush once 4 bytes have been sent.
+//
+#[ test ] fn bytes()
+{
+	let mut wrap = wrap( FlushPolicy::Bytes(4), 3 );
+
+	assert_matches!( write( &mut wrap, &[ 1, 2, 3 ] ), Poll::Ready(Ok(3)) );
+	assert_eq!( wrap.inner().poll_flush, 0 );
+
+	assert_matches!( write( &mut wrap, &[ 4, 5 ] ), Poll::Ready(Ok(2)) );
+	assert_eq!( wrap.inner().poll_flush, 1 );
+
+	assert_matches!( write( &mut wrap, &[ 6 ] ), Poll::Ready(Ok(1)) );
+	assert_eq!( wrap.inner().poll_flush, 1 );
+}
+
+
+// An explicit flush starts counting from zero again.
+//
+#[ test ] fn reset()
+{
+	let mut wrap = wrap( FlushPolicy::Items(2), 3 );
+
+	assert_matches!( write( &mut wrap, &[ 1 ] ), Poll::Ready(Ok(1))  );
+	assert_matches!( flush( &mut wrap         ), Poll::Ready(Ok(())) );
+	assert_matches!( write( &mut wrap, &[ 2 ] ), Poll::Ready(Ok(1))  );
+
+	assert_eq!( wrap.inner().poll_flush, 1 );
+
+	assert_matches!( write( &mut wrap, &[ 3 ] ), Poll::Ready(Ok(1)) );
+
+	assert_eq!( wrap.inner().poll_flush, 2 );
+}
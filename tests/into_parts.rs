@@ -0,0 +1,157 @@
+// Test IoStream::into_parts and IoStream::from_parts:
+//
+// ✔ the unread tail of a partially read item is returned
+// ✔ a buffered read error is returned
+// ✔ a deferred write error is returned
+// ✔ from_parts restores the tail so reading continues where it left off
+// ✔ from_parts restores a buffered error
+// ✔ from_parts discards a fully consumed chunk
+// ✔ a restored write buffer bigger than the maximum item size is sent in several items
+//
+mod common;
+
+use
+{
+	common            :: { *                                             } ,
+	async_io_stream   :: { *                                             } ,
+	futures           :: { *, task::noop_waker                           } ,
+	std               :: { task::{ Poll, Context }, pin::Pin, io::Cursor } ,
+	pretty_assertions :: { assert_eq                                     } ,
+	assert_matches    :: { assert_matches                                } ,
+};
+
+
+
+// Read part of an item and take it back out.
+//
+#[ test ] fn partial_item()
+{
+	let stream = TestStream::new( vec![ vec![ 1, 2, 3, 4 ].into(), vec![ 5, 6 ].into() ].into() );
+
+	let mut wrapped = IoStream::new( stream );
+	let     waker   = noop_waker();
+	let mut cx      = Context::from_waker( &waker );
+	let mut buf     = [ 0u8; 3 ];
+
+	assert_matches!( Pin::new( &mut wrapped ).poll_read( &mut cx, &mut buf ), Poll::Ready(Ok(3)) );
+
+	let parts = wrapped.into_parts();
+
+	assert_matches!( parts.read_state.front(), Some( ReadState::Ready{ chunk } ) =>
+	{
+		assert_eq!( chunk.position(), 3                   );
+		assert_eq!( chunk.get_ref() , &vec![ 1, 2, 3, 4 ] );
+	});
+
+	assert!( parts.write_err.is_none() );
+	assert_eq!( parts.inner.polled(), 1 );
+
+	// Restore and continue reading.
+	//
+	let mut wrapped = IoStream::from_parts( parts );
+	let mut buf     = [ 0u8; 3 ];
+
+	assert_matches!( Pin::new( &mut wrapped ).poll_read( &mut cx, &mut buf ), Poll::Ready(Ok(3)) );
+	assert_eq!( buf, [ 4, 5, 6 ] );
+}
+
+
+// A read error buffered after returning data must not be lost.
+//
+#[ test ] fn buffered_error()
+{
+	let actions = vec![ vec![ 1, 2 ].into(), Action::Error( io::ErrorKind::NotConnected ) ];
+
+	let mut wrapped = IoStream::new( TestStream::new( actions.into() ) );
+	let     waker   = noop_waker();
+	let mut cx      = Context::from_waker( &waker );
+	let mut buf     = [ 0u8; 5 ];
+
+	assert_matches!( Pin::new( &mut wrapped ).poll_read( &mut cx, &mut buf ), Poll::Ready(Ok(2)) );
+
+	let parts = wrapped.into_parts();
+
+	assert_matches!( parts.read_state.front(), Some( ReadState::Error{ error } ) => assert_eq!( error.kind(), io::ErrorKind::NotConnected ) );
+
+	let mut wrapped = IoStream::from_parts( parts );
+
+	assert_matches!
+	(
+		Pin::new( &mut wrapped ).poll_read( &mut cx, &mut buf ),
+		Poll::Ready(Err(e)) => assert_eq!( e.kind(), io::ErrorKind::NotConnected )
+	);
+}
+
+
+// A deferred error from the flush in poll_write must be handed back.
+//
+#[ test ] fn write_error()
+{
+	let ra = vec![ ReadyAction::Ok                                   ];
+	let sa = vec![ SendAction::Ok                                    ];
+	let fa = vec![ FlushAction::Error( io::ErrorKind::NotConnected ) ];
+
+	let mut wrapped = IoStream::new( TestSink::new( ra, sa, fa ) );
+	let     waker   = noop_waker();
+	let mut cx      = Context::from_waker( &waker );
+
+	assert_matches!( Pin::new( &mut wrapped ).poll_write( &mut cx, &[ 1, 2 ] ), Poll::Ready(Ok(2)) );
+
+	let parts = wrapped.into_parts();
+
+	assert!( parts.read_state.is_empty() );
+	assert_matches!( &parts.write_err, Some( e ) => assert_eq!( e.kind(), io::ErrorKind::NotConnected ) );
+	assert_eq!( parts.inner.items, vec![ vec![ 1, 2 ] ] );
+
+	let mut wrapped: IoStream<TestSink, Vec<u8>> = IoStream::from_parts( parts );
+
+	assert_matches!
+	(
+		Pin::new( &mut wrapped ).poll_write( &mut cx, &[ 3 ] ),
+		Poll::Ready(Err(e)) => assert_eq!( e.kind(), io::ErrorKind::NotConnected )
+	);
+}
+
+
+// A chunk without bytes left is not kept, otherwise poll_fill_buf would signal EOF.
+//
+#[ test ] fn consumed_chunk()
+{
+	let mut parts = IoParts::new( TestStream::new( vec![ vec![ 4, 5 ].into() ].into() ) );
+
+	let mut chunk = Cursor::new( vec![ 1, 2, 3 ] );
+	chunk.set_position( 3 );
+
+	parts.read_state.push_back( ReadState::Ready{ chunk } );
+
+	let mut wrapped = IoStream::from_parts( parts );
+	let     waker   = noop_waker();
+	let mut cx      = Context::from_waker( &waker );
+
+	assert_matches!( Pin::new( &mut wrapped ).poll_fill_buf( &mut cx ), Poll::Ready(Ok(data)) => assert_eq!( data, &[ 4, 5 ] ) );
+}
+
+
+
+// The write buffer can hold more than fits in one item, it must all be sent before new data is
+// taken.
+//
+#[ test ] fn oversized_write_buffer()
+{
+	let ra = vec![ ReadyAction::Ok; 4 ];
+	let sa = vec![ SendAction::Ok ; 4 ];
+	let fa = vec![ FlushAction::Ok; 4 ];
+
+	let mut parts = IoParts::new( TestSink::new( ra, sa, fa ) );
+
+	parts.write_buf = ( 1..=10 ).collect();
+
+	let mut wrapped: IoStream<TestSink, Vec<u8>> = IoStream::from_parts( parts ).with_max_item_size( 4 );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	assert_matches!( Pin::new( &mut wrapped ).poll_write( &mut cx, &[ 11, 12 ] ), Poll::Ready(Ok(2)) );
+
+	assert_eq!( wrapped.inner().items, vec![ vec![ 1, 2, 3, 4 ], vec![ 5, 6, 7, 8 ], vec![ 9, 10 ], vec![ 11, 12 ] ] );
+}
@@ -0,0 +1,91 @@
+// Test IoStream::join:
+//
+// ✔ reads come from the stream
+// ✔ writes go to the sink
+// ✔ errors from the stream and the sink are returned
+//
+mod common;
+
+use
+{
+	common            :: { *                                 } ,
+	async_io_stream   :: { *                                 } ,
+	futures           :: { *, task::noop_waker               } ,
+	std               :: { task::{ Poll, Context }, pin::Pin } ,
+	pretty_assertions :: { assert_eq                         } ,
+	assert_matches    :: { assert_matches                    } ,
+};
+
+
+
+// Read from the stream and write to the sink.
+//
+#[ test ] fn read_write()
+{
+	let stream = TestStream::new( vec![ vec![ 1, 2 ].into(), vec![ 3 ].into() ].into() );
+	let sink   = TestSink::new( vec![ ReadyAction::Ok ], vec![ SendAction::Ok ], vec![ FlushAction::Ok ] );
+
+	let mut wrapped = IoStream::join( stream, sink );
+	let     waker   = noop_waker();
+	let mut cx      = Context::from_waker( &waker );
+	let mut buf     = [ 0u8; 3 ];
+
+	assert_matches!( Pin::new( &mut wrapped ).poll_read ( &mut cx, &mut buf ), Poll::Ready(Ok(3)) );
+	assert_matches!( Pin::new( &mut wrapped ).poll_write( &mut cx, &[ 4, 5 ] ), Poll::Ready(Ok(2)) );
+
+	assert_eq!( buf, [ 1, 2, 3 ] );
+
+	let (stream, sink) = wrapped.into_parts().inner.into_inner();
+
+	assert_eq!( stream.polled(), 2                  );
+	assert_eq!( sink.items     , vec![ vec![ 4, 5 ] ] );
+}
+
+
+// Errors from both sides.
+//
+#[ test ] fn errors()
+{
+	let stream = TestStream::new( vec![ Action::Error( io::ErrorKind::ConnectionReset ) ].into() );
+	let sink   = TestSink::new( vec![ ReadyAction::Error( io::ErrorKind::NotConnected ) ], vec![], vec![] );
+
+	let mut wrapped = IoStream::join( stream, sink );
+	let     waker   = noop_waker();
+	let mut cx      = Context::from_waker( &waker );
+	let mut buf     = [ 0u8; 3 ];
+
+	assert_matches!
+	(
+		Pin::new( &mut wrapped ).poll_read( &mut cx, &mut buf ),
+		Poll::Ready(Err(e)) => assert_eq!( e.kind(), io::ErrorKind::ConnectionReset )
+	);
+
+	assert_matches!
+	(
+		Pin::new( &mut wrapped ).poll_write( &mut cx, &[ 1 ] ),
+		Poll::Ready(Err(e)) => assert_eq!( e.kind(), io::ErrorKind::NotConnected )
+	);
+}
+
+
+// Over a channel, like in the docs.
+//
+#[ test ] fn channel()
+{
+	let (tx, rx) = channel::mpsc::channel::< Vec<u8> >( 8 );
+
+	let stream = rx.map( Ok );
+	let sink   = tx.sink_map_err( |_| io::Error::from( io::ErrorKind::NotConnected ) );
+
+	let mut wrapped = IoStream::join( stream, sink );
+
+	executor::block_on( async
+	{
+		wrapped.write_all( &[ 1, 2, 3 ] ).await.expect( "write" );
+
+		let mut buf = [ 0u8; 3 ];
+		wrapped.read_exact( &mut buf ).await.expect( "read" );
+
+		assert_eq!( buf, [ 1, 2, 3 ] );
+	});
+}
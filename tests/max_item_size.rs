@@ -0,0 +1,104 @@
+// Test IoStream::with_max_item_size:
+//
+// ✔ a write bigger than the maximum is a short write
+// ✔ a vectored write bigger than the maximum is a short write
+// ✔ write_all splits the data over several items
+// ✔ the write buffer is capped to the maximum
+// ✔ a maximum of 0 panics
+//
+mod common;
+
+use
+{
+	common            :: { *                                              } ,
+	async_io_stream   :: { *                                              } ,
+	futures           :: { *, task::noop_waker                            } ,
+	std               :: { task::{ Poll, Context }, pin::Pin, io::IoSlice } ,
+	pretty_assertions :: { assert_eq                                      } ,
+	assert_matches    :: { assert_matches                                 } ,
+};
+
+
+
+// Only take what fits in an item.
+//
+#[ test ] fn short_write()
+{
+	let mut wrap = IoStream::new( TestSink::ok( 1 ) ).with_max_item_size( 3 );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_write( &mut cx, &[ 1, 2, 3, 4, 5 ] ), Poll::Ready(Ok(3)) );
+
+	assert_eq!( wrap.inner().items, vec![ vec![ 1, 2, 3 ] ] );
+}
+
+
+// Only take what fits in an item from several buffers.
+//
+#[ test ] fn short_write_vectored()
+{
+	let mut wrap = IoStream::new( TestSink::ok( 1 ) ).with_max_item_size( 3 );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+	let     bufs  = [ IoSlice::new( &[ 1, 2 ] ), IoSlice::new( &[ 3, 4 ] ) ];
+
+	assert_matches!( Pin::new( &mut wrap ).poll_write_vectored( &mut cx, &bufs ), Poll::Ready(Ok(3)) );
+
+	assert_eq!( wrap.inner().items, vec![ vec![ 1, 2, 3 ] ] );
+}
+
+
+// write_all keeps writing until everything is sent.
+//
+#[ test ] fn write_all()
+{
+	let (a, mut b) = IoStream::pair( 8 );
+	let mut a      = a.with_max_item_size( 3 );
+
+	executor::block_on( async
+	{
+		a.write_all( &[ 1, 2, 3, 4, 5, 6, 7 ] ).await.expect( "write_all" );
+		a.close().await.expect( "close" );
+
+		let mut items = Vec::new();
+
+		loop
+		{
+			let item = b.fill_buf().await.expect( "fill_buf" ).to_vec();
+
+			if item.is_empty() { break }
+
+			b.consume_unpin( item.len() );
+			items.push( item );
+		}
+
+		assert_eq!( items, vec![ vec![ 1, 2, 3 ], vec![ 4, 5, 6 ], vec![ 7 ] ] );
+	});
+}
+
+
+// The write buffer never grows past the maximum item size.
+//
+#[ test ] fn write_buffer()
+{
+	let mut wrap = IoStream::new( TestSink::ok( 1 ) ).with_write_buffer( 10 ).with_max_item_size( 3 );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_write( &mut cx, &[ 1, 2 ] ), Poll::Ready(Ok(2)) );
+	assert_matches!( Pin::new( &mut wrap ).poll_write( &mut cx, &[ 3, 4 ] ), Poll::Ready(Ok(1)) );
+
+	assert_eq!( wrap.inner().items, vec![ vec![ 1, 2, 3 ] ] );
+}
+
+
+// Items need to be able to hold some data.
+//
+#[ test ] #[ should_panic ] fn zero()
+{
+	let _ = IoStream::< TestSink, Vec<u8> >::new( TestSink::ok( 0 ) ).with_max_item_size( 0 );
+}
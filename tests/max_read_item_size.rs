@@ -0,0 +1,72 @@
+// Test IoStream::with_max_read_item_size:
+//
+// ✔ an item of exactly the maximum size is accepted
+// ✔ a bigger item gives InvalidData from poll_read, after the data read before it
+// ✔ a bigger item gives InvalidData from poll_fill_buf
+// ✔ reading continues with the next item
+//
+mod common;
+
+use
+{
+	common            :: { *                                 } ,
+	async_io_stream   :: { *                                 } ,
+	futures           :: { *, task::noop_waker               } ,
+	std               :: { task::{ Poll, Context }, pin::Pin } ,
+	pretty_assertions :: { assert_eq                         } ,
+	assert_matches    :: { assert_matches                    } ,
+};
+
+
+fn limited( actions: Vec<Action>, max: usize ) -> IoStream<TestStream, Vec<u8>>
+{
+	IoStream::new( TestStream::new( actions.into() ) ).with_max_read_item_size( max )
+}
+
+
+
+// poll_read returns the data before the oversized item, then the error, then the next item.
+//
+#[ test ] fn read()
+{
+	let actions = vec![ vec![ 1, 2, 3 ].into(), vec![ 1, 2, 3, 4 ].into(), vec![ 5 ].into() ];
+
+	let mut wrap = limited( actions, 3 );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+	let mut buf   = [ 0u8; 10 ];
+
+	assert_matches!( Pin::new( &mut wrap ).poll_read( &mut cx, &mut buf ), Poll::Ready(Ok(3)) );
+	assert_eq!( &buf[..3], &[ 1, 2, 3 ] );
+
+	assert_matches!
+	(
+		Pin::new( &mut wrap ).poll_read( &mut cx, &mut buf ),
+		Poll::Ready(Err(e)) => assert_eq!( e.kind(), io::ErrorKind::InvalidData )
+	);
+
+	assert_matches!( Pin::new( &mut wrap ).poll_read( &mut cx, &mut buf ), Poll::Ready(Ok(1)) );
+	assert_eq!( buf[0], 5 );
+}
+
+
+// poll_fill_buf returns the error, then the next item.
+//
+#[ test ] fn fill_buf()
+{
+	let actions = vec![ vec![ 1, 2, 3, 4 ].into(), vec![ 5 ].into() ];
+
+	let mut wrap = limited( actions, 3 );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	assert_matches!
+	(
+		Pin::new( &mut wrap ).poll_fill_buf( &mut cx ),
+		Poll::Ready(Err(e)) => assert_eq!( e.kind(), io::ErrorKind::InvalidData )
+	);
+
+	assert_matches!( Pin::new( &mut wrap ).poll_fill_buf( &mut cx ), Poll::Ready(Ok(data)) => assert_eq!( data, &[ 5 ] ) );
+}
@@ -0,0 +1,113 @@
+// Test IoStream::with_min_fill_buf:
+//
+// ✔ small items are merged up to the minimum
+// ✔ merging stops when the stream is pending
+// ✔ an error after merged bytes is returned after them
+// ✔ the end of the stream after merged bytes
+// ✔ read_line over many tiny items
+//
+mod common;
+
+use
+{
+	common            :: { *                                 } ,
+	async_io_stream   :: { *                                 } ,
+	futures           :: { *, task::noop_waker               } ,
+	std               :: { task::{ Poll, Context }, pin::Pin } ,
+	pretty_assertions :: { assert_eq                         } ,
+	assert_matches    :: { assert_matches                    } ,
+};
+
+
+
+fn fill( wrap: &mut IoStream<TestStream, Vec<u8>>, consume: usize ) -> Poll< io::Result<Vec<u8>> >
+{
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	let data = Pin::new( &mut *wrap ).poll_fill_buf( &mut cx ).map_ok( |data| data.to_vec() );
+
+	Pin::new( wrap ).consume( consume );
+
+	data
+}
+
+
+
+#[ test ] fn merge()
+{
+	let actions = vec![ vec![ 1 ].into(), vec![ 2 ].into(), vec![ 3 ].into(), vec![ 4, 5, 6, 7 ].into(), vec![ 8 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) ).with_min_fill_buf( 3 );
+
+	assert_matches!( fill( &mut wrap, 3 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 1, 2, 3    ] ) );
+	assert_matches!( fill( &mut wrap, 4 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 4, 5, 6, 7 ] ) );
+	assert_matches!( fill( &mut wrap, 1 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 8          ] ) );
+	assert_matches!( fill( &mut wrap, 0 ), Poll::Ready(Ok(data)) => assert!( data.is_empty() )              );
+}
+
+
+
+#[ test ] fn pending()
+{
+	let actions = vec![ vec![ 1 ].into(), Action::Pending, vec![ 2 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) ).with_min_fill_buf( 2 );
+
+	assert_matches!( fill( &mut wrap, 1 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 1 ] ) );
+	assert_matches!( fill( &mut wrap, 1 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 2 ] ) );
+}
+
+
+
+#[ test ] fn error()
+{
+	let actions = vec![ vec![ 1 ].into(), Action::Error( io::ErrorKind::ConnectionReset ), vec![ 2 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) ).with_min_fill_buf( 4 );
+
+	assert_matches!( fill( &mut wrap, 1 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 1 ] ) );
+
+	assert_matches!
+	(
+		fill( &mut wrap, 0 ),
+		Poll::Ready(Err(e)) => assert_eq!( e.kind(), io::ErrorKind::ConnectionReset )
+	);
+
+	assert_matches!( fill( &mut wrap, 1 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 2 ] ) );
+}
+
+
+
+#[ test ] fn eof()
+{
+	let actions = vec![ vec![ 1 ].into(), vec![ 2 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) ).with_min_fill_buf( 4 );
+
+	assert_matches!( fill( &mut wrap, 2 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 1, 2 ] ) );
+	assert_matches!( fill( &mut wrap, 0 ), Poll::Ready(Ok(data)) => assert!( data.is_empty() )     );
+
+	assert_eq!( wrap.inner().polled(), 3 );
+}
+
+
+
+#[ test ] fn read_line()
+{
+	let actions: Vec<Action> = "hello\nworld".bytes().map( |b| vec![ b ].into() ).collect();
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) ).with_min_fill_buf( 64 );
+	let mut line = String::new();
+
+	executor::block_on( async
+	{
+		wrap.read_line( &mut line ).await.expect( "read_line" );
+		assert_eq!( line, "hello\n" );
+
+		line.clear();
+
+		wrap.read_line( &mut line ).await.expect( "read_line" );
+		assert_eq!( line, "world" );
+	});
+}
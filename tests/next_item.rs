@@ -0,0 +1,87 @@
+// Test IoStream::poll_next_item and next_item:
+//
+// ✔ whole items are returned as is
+// ✔ the rest of a partially read item is returned first
+// ✔ reading bytes continues after reading items
+// ✔ a buffered error is returned
+// ✔ None at the end of the stream, also after that
+//
+mod common;
+
+use
+{
+	common            :: { *                                 } ,
+	async_io_stream   :: { *                                 } ,
+	futures           :: { *, task::noop_waker               } ,
+	std               :: { task::{ Poll, Context }, pin::Pin } ,
+	pretty_assertions :: { assert_eq                         } ,
+	assert_matches    :: { assert_matches                    } ,
+};
+
+
+
+// Interleave byte and item reads.
+//
+#[ test ] fn interleave()
+{
+	let actions = vec![ vec![ 1, 2, 3, 4 ].into(), vec![ 5, 6 ].into(), vec![ 7, 8 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) );
+
+	executor::block_on( async
+	{
+		let mut buf = [ 0u8; 2 ];
+
+		wrap.read_exact( &mut buf ).await.expect( "read" );
+		assert_eq!( buf, [ 1, 2 ] );
+
+		assert_eq!( wrap.next_item().await.expect( "item" ).expect( "no error" ), vec![ 3, 4 ] );
+		assert_eq!( wrap.next_item().await.expect( "item" ).expect( "no error" ), vec![ 5, 6 ] );
+
+		wrap.read_exact( &mut buf ).await.expect( "read" );
+		assert_eq!( buf, [ 7, 8 ] );
+
+		assert!( wrap.next_item().await.is_none() );
+		assert!( wrap.next_item().await.is_none() );
+	});
+}
+
+
+// A read buffers an error when the stream returns one after data, next_item returns it.
+//
+#[ test ] fn buffered_error()
+{
+	let actions = vec![ vec![ 1 ].into(), Action::Error( io::ErrorKind::NotConnected ), vec![ 2 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+	let mut buf   = [ 0u8; 4 ];
+
+	assert_matches!( Pin::new( &mut wrap ).poll_read( &mut cx, &mut buf ), Poll::Ready(Ok(1)) );
+
+	assert_matches!
+	(
+		Pin::new( &mut wrap ).poll_next_item( &mut cx ),
+		Poll::Ready(Some(Err(e))) => assert_eq!( e.kind(), io::ErrorKind::NotConnected )
+	);
+
+	assert_matches!( Pin::new( &mut wrap ).poll_next_item( &mut cx ), Poll::Ready(Some(Ok(item))) => assert_eq!( item, vec![ 2 ] ) );
+}
+
+
+// Pending from the stream is returned.
+//
+#[ test ] fn pending()
+{
+	let actions = vec![ Action::Pending, vec![ 1 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_next_item( &mut cx ), Poll::Pending );
+	assert_matches!( Pin::new( &mut wrap ).poll_next_item( &mut cx ), Poll::Ready(Some(Ok(item))) => assert_eq!( item, vec![ 1 ] ) );
+}
@@ -0,0 +1,91 @@
+// Test IoStream::pair:
+//
+// ✔ data written on one end is read on the other, in both directions
+// ✔ item boundaries are preserved
+// ✔ writes return pending when the channel is full
+// ✔ closing one end gives EOF on the other and makes writes fail
+//
+use
+{
+	async_io_stream   :: { *                                 } ,
+	futures           :: { *, task::noop_waker               } ,
+	std               :: { task::{ Poll, Context }, pin::Pin } ,
+	pretty_assertions :: { assert_eq                         } ,
+	assert_matches    :: { assert_matches                    } ,
+};
+
+
+
+// Both directions work.
+//
+#[ test ] fn both_directions()
+{
+	let (mut a, mut b) = IoStream::pair( 4 );
+
+	executor::block_on( async
+	{
+		a.write_all( b"ping" ).await.expect( "write a" );
+		b.write_all( b"pong" ).await.expect( "write b" );
+
+		let mut buf = [ 0u8; 4 ];
+
+		b.read_exact( &mut buf ).await.expect( "read b" ); assert_eq!( &buf, b"ping" );
+		a.read_exact( &mut buf ).await.expect( "read a" ); assert_eq!( &buf, b"pong" );
+	});
+}
+
+
+// Every write is one item on the other side.
+//
+#[ test ] fn boundaries()
+{
+	let (mut a, mut b) = IoStream::pair( 4 );
+
+	executor::block_on( async
+	{
+		a.write_all( &[ 1, 2 ] ).await.expect( "write" );
+		a.write_all( &[ 3    ] ).await.expect( "write" );
+
+		assert_eq!( b.fill_buf().await.expect( "fill_buf" ), &[ 1, 2 ] );
+		b.consume_unpin( 2 );
+		assert_eq!( b.fill_buf().await.expect( "fill_buf" ), &[ 3 ] );
+	});
+}
+
+
+// With a capacity of 0, one item can be in flight.
+//
+#[ test ] fn backpressure()
+{
+	let (mut a, _b) = IoStream::pair( 0 );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	assert_matches!( Pin::new( &mut a ).poll_write( &mut cx, &[ 1 ] ), Poll::Ready(Ok(1)) );
+	assert_matches!( Pin::new( &mut a ).poll_write( &mut cx, &[ 2 ] ), Poll::Pending      );
+}
+
+
+// Close one end.
+//
+#[ test ] fn close()
+{
+	let (mut a, mut b) = IoStream::pair( 4 );
+
+	executor::block_on( async
+	{
+		a.write_all( &[ 1 ] ).await.expect( "write" );
+		a.close().await.expect( "close" );
+
+		let mut buf = Vec::new();
+
+		b.read_to_end( &mut buf ).await.expect( "read" );
+		assert_eq!( buf, vec![ 1 ] );
+
+		drop( a );
+
+		let err = b.write_all( &[ 2 ] ).await.expect_err( "peer is gone" );
+		assert_eq!( err.kind(), io::ErrorKind::NotConnected );
+	});
+}
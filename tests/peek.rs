@@ -0,0 +1,142 @@
+// Test IoStream::poll_peek:
+//
+// ✔ peek across several items, later reads return the same bytes
+// ✔ pending until enough bytes are buffered
+// ✔ fewer bytes at the end of the stream
+// ✔ an error after some bytes is returned after them, an error before any bytes right away
+// ✔ an error that a read stopped in front of is returned
+// ✔ poll_fill_buf returns the peeked bytes as one item, poll_next_item the rest of it
+//
+mod common;
+
+use
+{
+	common            :: { *                                 } ,
+	async_io_stream   :: { *                                 } ,
+	futures           :: { *, task::noop_waker               } ,
+	std               :: { task::{ Poll, Context }, pin::Pin } ,
+	pretty_assertions :: { assert_eq                         } ,
+	assert_matches    :: { assert_matches                    } ,
+};
+
+
+
+fn peek( wrap: &mut IoStream<TestStream, Vec<u8>>, n: usize ) -> Poll< io::Result<Vec<u8>> >
+{
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	Pin::new( wrap ).poll_peek( &mut cx, n ).map_ok( |data| data.to_vec() )
+}
+
+
+
+#[ test ] fn across_items()
+{
+	let actions = vec![ vec![ 1 ].into(), vec![ 2, 3 ].into(), vec![ 4, 5, 6 ].into(), vec![ 7 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) );
+
+	assert_matches!( peek( &mut wrap, 4 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 1, 2, 3, 4, 5, 6 ] ) );
+	assert_matches!( peek( &mut wrap, 2 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 1, 2, 3, 4, 5, 6 ] ) );
+
+	assert_eq!( wrap.inner().polled(), 3 );
+
+	assert_matches!( read( &mut wrap, 4 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 1, 2, 3, 4 ] ) );
+	assert_matches!( read( &mut wrap, 4 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 5, 6, 7    ] ) );
+}
+
+
+
+#[ test ] fn pending()
+{
+	let actions = vec![ vec![ 1 ].into(), Action::Pending, vec![ 2 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) );
+
+	assert_matches!( peek( &mut wrap, 2 ), Poll::Pending );
+	assert_matches!( peek( &mut wrap, 2 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 1, 2 ] ) );
+}
+
+
+
+#[ test ] fn eof()
+{
+	let mut wrap = IoStream::new( TestStream::new( vec![ vec![ 1, 2 ].into() ].into() ) );
+
+	assert_matches!( peek( &mut wrap, 5 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 1, 2 ] ) );
+	assert_matches!( read( &mut wrap, 5 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 1, 2 ] ) );
+	assert_matches!( read( &mut wrap, 5 ), Poll::Ready(Ok(data)) => assert!( data.is_empty() )       );
+	assert_matches!( peek( &mut wrap, 5 ), Poll::Ready(Ok(data)) => assert!( data.is_empty() )       );
+}
+
+
+
+#[ test ] fn error()
+{
+	let actions = vec![ Action::Error( io::ErrorKind::NotConnected ), vec![ 1 ].into(), Action::Error( io::ErrorKind::ConnectionReset ), vec![ 2 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) );
+
+	assert_matches!
+	(
+		peek( &mut wrap, 3 ),
+		Poll::Ready(Err(e)) => assert_eq!( e.kind(), io::ErrorKind::NotConnected )
+	);
+
+	assert_matches!( peek( &mut wrap, 3 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 1 ] ) );
+	assert_matches!( read( &mut wrap, 3 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 1 ] ) );
+
+	assert_matches!
+	(
+		read( &mut wrap, 3 ),
+		Poll::Ready(Err(e)) => assert_eq!( e.kind(), io::ErrorKind::ConnectionReset )
+	);
+
+	assert_matches!( read( &mut wrap, 3 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 2 ] ) );
+}
+
+
+
+// After a read returned the bytes in front of an error, peek must return the error rather than
+// an empty slice, which would look like the end of the stream.
+//
+#[ test ] fn error_after_read()
+{
+	let actions = vec![ vec![ 1 ].into(), Action::Error( io::ErrorKind::BrokenPipe ), vec![ 2 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) );
+
+	assert_matches!( read( &mut wrap, 4 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 1 ] ) );
+
+	assert_eq!( wrap.state().error, Some( io::ErrorKind::BrokenPipe ) );
+
+	assert_matches!
+	(
+		peek( &mut wrap, 4 ),
+		Poll::Ready(Err(e)) => assert_eq!( e.kind(), io::ErrorKind::BrokenPipe )
+	);
+
+	assert_matches!( peek( &mut wrap, 4 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 2 ] ) );
+}
+
+
+
+#[ test ] fn fill_buf_next_item()
+{
+	let actions = vec![ vec![ 1 ].into(), vec![ 2 ].into(), vec![ 3 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	assert_matches!( peek( &mut wrap, 2 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 1, 2 ] ) );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_fill_buf( &mut cx ), Poll::Ready(Ok(data)) => assert_eq!( data, &[ 1, 2 ] ) );
+
+	Pin::new( &mut wrap ).consume( 1 );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_next_item( &mut cx ), Poll::Ready(Some(Ok(item))) => assert_eq!( item, vec![ 2 ] ) );
+	assert_matches!( Pin::new( &mut wrap ).poll_next_item( &mut cx ), Poll::Ready(Some(Ok(item))) => assert_eq!( item, vec![ 3 ] ) );
+}
@@ -0,0 +1,135 @@
+// Test that an implicit flush that returned pending is driven to completion:
+//
+// ✔ by the next read
+// ✔ by the next write, which returns the error from the flush
+// ✔ an explicit flush completes it, so it isn't polled again
+// ✔ the sink gets the waker of the task that polls the read or write
+//
+mod common;
+
+use
+{
+	common            :: { *                                                             } ,
+	async_io_stream   :: { *                                                             } ,
+	futures           :: { *, task::{ self, noop_waker, ArcWake }                        } ,
+	std               :: { task::{ Poll, Context }, pin::Pin, sync::{ Arc, atomic::* } } ,
+	pretty_assertions :: { assert_eq                                                     } ,
+	assert_matches    :: { assert_matches                                                } ,
+};
+
+
+
+// Counts how often the task is woken up.
+//
+#[ derive( Default ) ]
+//
+struct CountWaker
+{
+	woken: AtomicUsize,
+}
+
+impl ArcWake for CountWaker
+{
+	fn wake_by_ref( arc_self: &Arc<Self> )
+	{
+		arc_self.woken.fetch_add( 1, Ordering::SeqCst );
+	}
+}
+
+
+
+// A read drives the pending flush.
+//
+#[ test ] fn read()
+{
+	let mut wrap = transport( vec![ vec![ 1 ].into() ], vec![ FlushAction::Pending, FlushAction::Ok ] );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+	let mut buf   = [ 0u8; 4 ];
+
+	assert_matches!( Pin::new( &mut wrap ).poll_write( &mut cx, &[ 1 ] ), Poll::Ready(Ok(1)) );
+	assert_eq!( wrap.inner().sink.poll_flush, 1 );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_read( &mut cx, &mut buf ), Poll::Ready(Ok(1)) );
+	assert_eq!( wrap.inner().sink.poll_flush, 2 );
+
+	// The flush completed, so it's not polled again.
+	//
+	assert_matches!( Pin::new( &mut wrap ).poll_read( &mut cx, &mut buf ), Poll::Ready(Ok(0)) );
+	assert_eq!( wrap.inner().sink.poll_flush, 2 );
+}
+
+
+// The next write drives the flush and returns its error.
+//
+#[ test ] fn write_error()
+{
+	let mut wrap = transport( vec![], vec![ FlushAction::Pending, FlushAction::Error( io::ErrorKind::NotConnected ) ] );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_write( &mut cx, &[ 1 ] ), Poll::Ready(Ok(1)) );
+
+	assert_matches!
+	(
+		Pin::new( &mut wrap ).poll_write( &mut cx, &[ 2 ] ),
+		Poll::Ready(Err(e)) => assert_eq!( e.kind(), io::ErrorKind::NotConnected )
+	);
+
+	assert_eq!( wrap.inner().sink.poll_ready, 1                );
+	assert_eq!( wrap.inner().sink.items     , vec![ vec![ 1 ] ] );
+}
+
+
+// Once poll_flush returned ready, the implicit flush is no longer pending.
+//
+#[ test ] fn explicit_flush()
+{
+	let mut wrap = transport( vec![], vec![ FlushAction::Pending, FlushAction::Ok ] );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+	let mut buf   = [ 0u8; 4 ];
+
+	assert_matches!( Pin::new( &mut wrap ).poll_write( &mut cx, &[ 1 ] ), Poll::Ready(Ok(1))  );
+	assert_matches!( Pin::new( &mut wrap ).poll_flush( &mut cx         ), Poll::Ready(Ok(())) );
+	assert_matches!( Pin::new( &mut wrap ).poll_read ( &mut cx, &mut buf ), Poll::Ready(Ok(0))  );
+
+	assert_eq!( wrap.inner().sink.poll_flush, 2 );
+}
+
+
+// When the pending flush is driven from poll_read or poll_write, the sink must be able to wake up the
+// task that polled them, not the one that wrote the data.
+//
+#[ test ] fn wake_task()
+{
+	let ra = vec![ ReadyAction::Ok, ReadyAction::Pending ];
+	let sa = vec![ SendAction::Ok ];
+	let fa = vec![ FlushAction::Pending; 3 ];
+
+	let mut wrap = IoStream::new( TestTransport::new( TestStream::new( vec![ Action::Pending ].into() ), TestSink::new( ra, sa, fa ) ) );
+
+	let     noop   = noop_waker();
+	let mut cx     = Context::from_waker( &noop );
+	let mut buf    = [ 0u8; 4 ];
+	let     count  = Arc::new( CountWaker::default() );
+	let     waker  = task::waker( count.clone() );
+	let mut cx_cnt = Context::from_waker( &waker );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_write( &mut cx, &[ 1 ] ), Poll::Ready(Ok(1)) );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_read( &mut cx_cnt, &mut buf ), Poll::Pending );
+	assert_eq!( wrap.inner().sink.poll_flush, 2 );
+
+	wrap.inner_mut().sink.flush_waker.take().expect( "flush waker" ).wake();
+	assert_eq!( count.woken.load( Ordering::SeqCst ), 1 );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_write( &mut cx_cnt, &[ 2 ] ), Poll::Pending );
+	assert_eq!( wrap.inner().sink.poll_flush, 3 );
+
+	wrap.inner_mut().sink.flush_waker.take().expect( "flush waker" ).wake();
+	assert_eq!( count.woken.load( Ordering::SeqCst ), 2 );
+}
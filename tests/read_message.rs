@@ -0,0 +1,87 @@
+// Test IoStream::poll_read_message and read_message:
+//
+// ✔ reads never cross item boundaries and report the end of an item
+// ✔ an empty item is reported as such
+// ✔ an error from the stream is returned
+// ✔ the end of the stream returns 0 bytes without end of item, also after that
+//
+mod common;
+
+use
+{
+	common            :: { *                                 } ,
+	async_io_stream   :: { *                                 } ,
+	futures           :: { *, task::noop_waker               } ,
+	std               :: { task::{ Poll, Context }, pin::Pin } ,
+	pretty_assertions :: { assert_eq                         } ,
+	assert_matches    :: { assert_matches                    } ,
+};
+
+
+
+// Read two items with a buffer smaller than the first.
+//
+#[ test ] fn boundaries()
+{
+	let actions = vec![ vec![ 1, 2, 3 ].into(), vec![ 4, 5 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) );
+
+	executor::block_on( async
+	{
+		let mut buf = [ 0u8; 2 ];
+
+		assert_eq!( wrap.read_message( &mut buf ).await.expect( "read" ), ( 2, false ) );
+		assert_eq!( buf, [ 1, 2 ] );
+
+		assert_eq!( wrap.read_message( &mut buf ).await.expect( "read" ), ( 1, true ) );
+		assert_eq!( buf[..1], [ 3 ] );
+
+		assert_eq!( wrap.read_message( &mut buf ).await.expect( "read" ), ( 2, true ) );
+		assert_eq!( buf, [ 4, 5 ] );
+
+		assert_eq!( wrap.read_message( &mut buf ).await.expect( "read" ), ( 0, false ) );
+		assert_eq!( wrap.read_message( &mut buf ).await.expect( "read" ), ( 0, false ) );
+	});
+}
+
+
+// An empty item ends immediately.
+//
+#[ test ] fn empty_item()
+{
+	let actions = vec![ vec![].into(), vec![ 1 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+	let mut buf   = [ 0u8; 2 ];
+
+	assert_matches!( Pin::new( &mut wrap ).poll_read_message( &mut cx, &mut buf ), Poll::Ready(Ok(( 0, true ))) );
+	assert_matches!( Pin::new( &mut wrap ).poll_read_message( &mut cx, &mut buf ), Poll::Ready(Ok(( 1, true ))) );
+}
+
+
+// Errors and pending from the stream are returned.
+//
+#[ test ] fn error()
+{
+	let actions = vec![ Action::Pending, Action::Error( io::ErrorKind::ConnectionReset ), vec![ 1 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+	let mut buf   = [ 0u8; 2 ];
+
+	assert_matches!( Pin::new( &mut wrap ).poll_read_message( &mut cx, &mut buf ), Poll::Pending );
+
+	assert_matches!
+	(
+		Pin::new( &mut wrap ).poll_read_message( &mut cx, &mut buf ),
+		Poll::Ready(Err(e)) => assert_eq!( e.kind(), io::ErrorKind::ConnectionReset )
+	);
+
+	assert_matches!( Pin::new( &mut wrap ).poll_read_message( &mut cx, &mut buf ), Poll::Ready(Ok(( 1, true ))) );
+}
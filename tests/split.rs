@@ -0,0 +1,88 @@
+// Test IoStream::split and reunite:
+//
+// ✔ both halves work on the shared transport
+// ✔ buffered read data goes to the read half and survives reunite
+// ✔ a deferred write error goes to the write half
+// ✔ halves from different streams can not be reunited
+//
+mod common;
+
+use
+{
+	common            :: { *                                 } ,
+	async_io_stream   :: { *                                 } ,
+	futures           :: { *, task::noop_waker               } ,
+	std               :: { task::{ Poll, Context }, pin::Pin } ,
+	pretty_assertions :: { assert_eq                         } ,
+	assert_matches    :: { assert_matches                    } ,
+};
+
+
+
+// Read and write through the halves, then put them back together.
+//
+#[ test ] fn read_write_reunite()
+{
+	let wrapped = transport( vec![ vec![ 1, 2, 3 ].into(), vec![ 4, 5 ].into() ], vec![ FlushAction::Ok ] );
+
+	let (mut reader, mut writer) = wrapped.split();
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+	let mut buf   = [ 0u8; 2 ];
+
+	assert_matches!( Pin::new( &mut reader ).poll_read ( &mut cx, &mut buf ), Poll::Ready(Ok(2)) );
+	assert_matches!( Pin::new( &mut writer ).poll_write( &mut cx, &[ 9, 9 ] ), Poll::Ready(Ok(2)) );
+
+	assert_eq!( buf, [ 1, 2 ] );
+	assert!( reader.is_pair_of( &writer ) );
+
+	let mut wrapped = reader.reunite( writer ).expect( "reunite" );
+
+	assert_eq!( wrapped.inner().sink.items, vec![ vec![ 9, 9 ] ] );
+
+	// The rest of the first item was kept by the read half.
+	//
+	let mut buf = [ 0u8; 3 ];
+
+	assert_matches!( Pin::new( &mut wrapped ).poll_read( &mut cx, &mut buf ), Poll::Ready(Ok(3)) );
+	assert_eq!( buf, [ 3, 4, 5 ] );
+}
+
+
+// The error from the implicit flush is returned from the next write on the write half.
+//
+#[ test ] fn write_error()
+{
+	let wrapped = transport( vec![], vec![ FlushAction::Error( io::ErrorKind::NotConnected ), FlushAction::Ok ] );
+
+	let (_reader, mut writer) = wrapped.split();
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	assert_matches!( Pin::new( &mut writer ).poll_write( &mut cx, &[ 1 ] ), Poll::Ready(Ok(1)) );
+
+	assert_matches!
+	(
+		Pin::new( &mut writer ).poll_write( &mut cx, &[ 2 ] ),
+		Poll::Ready(Err(e)) => assert_eq!( e.kind(), io::ErrorKind::NotConnected )
+	);
+}
+
+
+// Halves of different streams.
+//
+#[ test ] fn reunite_mismatch()
+{
+	let (reader_a, writer_a) = transport( vec![], vec![] ).split();
+	let (reader_b, writer_b) = transport( vec![], vec![] ).split();
+
+	assert!( !reader_a.is_pair_of( &writer_b ) );
+	assert!( !writer_a.is_pair_of( &reader_b ) );
+
+	let ReuniteError( reader_a, writer_b ) = reader_a.reunite( writer_b ).expect_err( "mismatched halves" );
+
+	assert!( reader_a.reunite( writer_a ).is_ok() );
+	assert!( writer_b.reunite( reader_b ).is_ok() );
+}
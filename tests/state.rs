@@ -0,0 +1,67 @@
+// Test IoStream::state and the Debug impl:
+//
+// ✔ nothing buffered on a new IoStream
+// ✔ the unread bytes of a partially read item
+// ✔ an error buffered for the next read
+// ✔ the end of the stream
+// ✔ Debug shows the type of the inner stream and the read state, which doesn't need to implement Debug
+//
+mod common;
+
+use
+{
+	common            :: { *                                 } ,
+	async_io_stream   :: { *                                 } ,
+	futures           :: { *, task::noop_waker               } ,
+	std               :: { task::{ Poll, Context }, pin::Pin } ,
+	pretty_assertions :: { assert_eq                         } ,
+	assert_matches    :: { assert_matches                    } ,
+};
+
+
+
+#[ test ] fn state()
+{
+	let actions = vec![ vec![ 1, 2, 3 ].into(), vec![ 4 ].into(), Action::Error( io::ErrorKind::ConnectionReset ) ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+	let mut buf   = [ 0u8; 1 ];
+
+	assert_eq!( wrap.state(), IoState::default() );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_read( &mut cx, &mut buf ), Poll::Ready(Ok(1)) );
+
+	assert_eq!( wrap.state(), IoState{ buffered: 2, error: None, eof: false } );
+
+	let mut buf = [ 0u8; 4 ];
+
+	assert_matches!( Pin::new( &mut wrap ).poll_read( &mut cx, &mut buf ), Poll::Ready(Ok(3)) );
+
+	assert_eq!( wrap.state(), IoState{ buffered: 0, error: Some( io::ErrorKind::ConnectionReset ), eof: false } );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_read( &mut cx, &mut buf ), Poll::Ready(Err(_)) );
+	assert_matches!( Pin::new( &mut wrap ).poll_read( &mut cx, &mut buf ), Poll::Ready(Ok(0)) );
+
+	assert_eq!( wrap.state(), IoState{ buffered: 0, error: None, eof: true } );
+}
+
+
+
+#[ test ] fn debug()
+{
+	let mut wrap = IoStream::new( TestStream::new( vec![ vec![ 1, 2, 3 ].into() ].into() ) );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+	let mut buf   = [ 0u8; 1 ];
+
+	assert_matches!( Pin::new( &mut wrap ).poll_read( &mut cx, &mut buf ), Poll::Ready(Ok(1)) );
+
+	let debug = format!( "{wrap:?}" );
+
+	assert!( debug.starts_with( "IoStream { inner: " ), "{debug}" );
+	assert!( debug.contains( "TestStream, queued: 1, error: None, eof: false" ), "{debug}" );
+}
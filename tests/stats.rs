@@ -0,0 +1,114 @@
+// Test IoStream::stats:
+//
+// ✔ items and bytes received from the stream, and the biggest one
+// ✔ errors buffered by a read
+// ✔ items and bytes sent to the sink, and the biggest one
+// ✔ flushes, sink not ready and errors buffered by a write
+// ✔ each half only has the counters of its own direction
+// ✔ reunite keeps the counters of both halves
+//
+mod common;
+
+use
+{
+	common            :: { *                                 } ,
+	async_io_stream   :: { *                                 } ,
+	futures           :: { *, task::noop_waker               } ,
+	std               :: { task::{ Poll, Context }, pin::Pin } ,
+	pretty_assertions :: { assert_eq                         } ,
+	assert_matches    :: { assert_matches                    } ,
+};
+
+
+
+// Read two items in one call, then a deferred error.
+//
+#[ test ] fn read()
+{
+	let actions = vec![ vec![ 1, 2, 3 ].into(), vec![ 4 ].into(), vec![ 5 ].into(), Action::Error( io::ErrorKind::ConnectionReset ) ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+	let mut buf   = [ 0u8; 4 ];
+
+	assert_matches!( Pin::new( &mut wrap ).poll_read( &mut cx, &mut buf ), Poll::Ready(Ok(4)) );
+	assert_matches!( Pin::new( &mut wrap ).poll_read( &mut cx, &mut buf ), Poll::Ready(Ok(1)) );
+
+	let stats = wrap.stats();
+
+	assert_eq!( stats.items_read       , 3 );
+	assert_eq!( stats.bytes_read       , 5 );
+	assert_eq!( stats.largest_item_read, 3 );
+	assert_eq!( stats.read_errors      , 1 );
+	assert_eq!( stats.items_written    , 0 );
+}
+
+
+// A write that is pending, two that succeed, of which the last has a failing flush.
+//
+#[ test ] fn write()
+{
+	let ra = vec![ ReadyAction::Pending, ReadyAction::Ok, ReadyAction::Ok ];
+	let sa = vec![ SendAction::Ok ; 2 ];
+	let fa = vec![ FlushAction::Ok, FlushAction::Error( io::ErrorKind::NotConnected ) ];
+
+	let mut wrap = IoStream::new( TestSink::new( ra, sa, fa ) );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_write( &mut cx, &[ 1, 2 ] ), Poll::Pending      );
+	assert_matches!( Pin::new( &mut wrap ).poll_write( &mut cx, &[ 1, 2 ] ), Poll::Ready(Ok(2)) );
+	assert_matches!( Pin::new( &mut wrap ).poll_write( &mut cx, &[ 3    ] ), Poll::Ready(Ok(1)) );
+
+	let stats = wrap.stats();
+
+	assert_eq!( stats.items_written       , 2 );
+	assert_eq!( stats.bytes_written       , 3 );
+	assert_eq!( stats.largest_item_written, 2 );
+	assert_eq!( stats.flushes             , 1 );
+	assert_eq!( stats.ready_pending       , 1 );
+	assert_eq!( stats.write_errors        , 1 );
+	assert_eq!( stats.items_read          , 0 );
+}
+
+
+// Read and write through the halves, each counts its own direction.
+//
+#[ test ] fn split()
+{
+	let ra = vec![ ReadyAction::Ok ];
+	let sa = vec![ SendAction::Ok  ];
+	let fa = vec![ FlushAction::Ok ];
+
+	let wrap = IoStream::new( TestTransport::new( TestStream::new( vec![ vec![ 1 ].into() ].into() ), TestSink::new( ra, sa, fa ) ) );
+
+	let (mut reader, mut writer) = wrap.split();
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+	let mut buf   = [ 0u8; 1 ];
+
+	assert_matches!( Pin::new( &mut reader ).poll_read ( &mut cx, &mut buf  ), Poll::Ready(Ok(1)) );
+	assert_matches!( Pin::new( &mut writer ).poll_write( &mut cx, &[ 1, 2 ] ), Poll::Ready(Ok(2)) );
+
+	assert_eq!( reader.stats(), Stats{ items_read: 1, bytes_read: 1, largest_item_read: 1, ..Stats::default() } );
+
+	assert_eq!( writer.stats(), Stats
+	{
+		items_written       : 1,
+		bytes_written       : 2,
+		largest_item_written: 2,
+		flushes             : 1,
+		..Stats::default()
+	});
+
+	let stats = reader.reunite( writer ).expect( "reunite" ).stats();
+
+	assert_eq!( stats.items_read   , 1 );
+	assert_eq!( stats.items_written, 1 );
+	assert_eq!( stats.bytes_written, 2 );
+	assert_eq!( stats.flushes      , 1 );
+}
@@ -0,0 +1,115 @@
+// Test the Stream and Sink impls of IoStream::items:
+//
+// ✔ the stream returns the rest of a partially read item first
+// ✔ the sink returns a deferred write error first
+// ✔ the sink sends the write buffer before new items
+// ✔ items sent through the sink are counted for the flush policy
+// ✔ flush and close are not ambiguous
+//
+mod common;
+
+use
+{
+	common            :: { *                                 } ,
+	async_io_stream   :: { *                                 } ,
+	futures           :: { *, task::noop_waker               } ,
+	std               :: { task::{ Poll, Context }, pin::Pin } ,
+	pretty_assertions :: { assert_eq                         } ,
+	assert_matches    :: { assert_matches                    } ,
+};
+
+
+
+// Read part of an item as bytes, then the rest as items.
+//
+#[ test ] fn stream()
+{
+	let mut wrap = transport( vec![ vec![ 1, 2, 3 ].into(), vec![ 4, 5 ].into() ], vec![] );
+
+	executor::block_on( async
+	{
+		let mut buf = [ 0u8; 2 ];
+
+		wrap.read_exact( &mut buf ).await.expect( "read" );
+
+		assert_eq!( wrap.items().next().await.expect( "item" ).expect( "no error" ), vec![ 3    ] );
+		assert_eq!( wrap.items().next().await.expect( "item" ).expect( "no error" ), vec![ 4, 5 ] );
+		assert!   ( wrap.items().next().await.is_none() );
+	});
+}
+
+
+// The error from the implicit flush of a write is returned before sending an item.
+//
+#[ test ] fn write_error()
+{
+	let mut wrap = transport( vec![], vec![ FlushAction::Error( io::ErrorKind::NotConnected ), FlushAction::Ok ] );
+
+	executor::block_on( async
+	{
+		wrap.write_all( &[ 1 ] ).await.expect( "write" );
+
+		let err = wrap.items().send( vec![ 2 ] ).await.expect_err( "deferred error" );
+
+		assert_eq!( err.kind()             , io::ErrorKind::NotConnected );
+		assert_eq!( wrap.inner().sink.items, vec![ vec![ 1 ] ]           );
+	});
+}
+
+
+// Buffered bytes are sent before an item.
+//
+#[ test ] fn write_buffer()
+{
+	let mut wrap = transport( vec![], vec![ FlushAction::Ok; 2 ] ).with_write_buffer( 8 );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_write( &mut cx, &[ 1, 2 ] ), Poll::Ready(Ok(2)) );
+
+	executor::block_on( async
+	{
+		wrap.items().send( vec![ 3 ] ).await.expect( "send" );
+	});
+
+	assert_eq!( wrap.inner().sink.items, vec![ vec![ 1, 2 ], vec![ 3 ] ] );
+}
+
+
+// Items from the sink count towards the flush policy of writes.
+//
+#[ test ] fn flush_policy()
+{
+	let mut wrap = transport( vec![], vec![ FlushAction::Ok; 2 ] ).with_flush_policy( FlushPolicy::Items(2) );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	assert_matches!( Pin::new( &mut wrap.items() ).poll_ready( &mut cx ), Poll::Ready(Ok(())) );
+	assert_matches!( Pin::new( &mut wrap.items() ).start_send( vec![ 1 ] ), Ok(())              );
+
+	assert_eq!( wrap.inner().sink.poll_flush, 0 );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_write( &mut cx, &[ 2 ] ), Poll::Ready(Ok(1)) );
+
+	assert_eq!( wrap.inner().sink.poll_flush, 1 );
+}
+
+
+// Flush and close of AsyncWriteExt can be called with the Stream and Sink traits in scope.
+//
+#[ test ] fn no_ambiguity()
+{
+	let mut wrap = transport( vec![], vec![ FlushAction::Ok; 3 ] );
+
+	executor::block_on( async
+	{
+		wrap.items().send( vec![ 1 ] ).await.expect( "send" );
+		wrap.write_all( &[ 2 ] ).await.expect( "write" );
+		wrap.flush().await.expect( "flush" );
+		wrap.close().await.expect( "close" );
+	});
+
+	assert_eq!( wrap.inner().sink.items, vec![ vec![ 1 ], vec![ 2 ] ] );
+}
@@ -0,0 +1,212 @@
+// Test TextStream:
+//
+// ✔ a code point split over two writes ends up in one item
+// ✔ a code point split over two buffers of a vectored write
+// ✔ invalid UTF-8 is refused
+// ✔ invalid UTF-8 after an incomplete code point is refused
+// ✔ the write buffer only sends whole code points
+// ✔ the maximum item size doesn't split code points
+// ✔ flush keeps an incomplete code point, close fails on it
+// ✔ text mode needs a maximum item size of at least 4
+// ✔ String items over a channel, written and read back
+// ✔ items that are only AsRef<str> can be read
+//
+mod common;
+
+use
+{
+	common            :: { *                                              } ,
+	async_io_stream   :: { *                                              } ,
+	futures           :: { *, task::noop_waker, channel::mpsc             } ,
+	std               :: { task::{ Poll, Context }, pin::Pin, io::IoSlice } ,
+	std               :: { sync::Arc                                      } ,
+	pretty_assertions :: { assert_eq                                      } ,
+	assert_matches    :: { assert_matches                                 } ,
+};
+
+
+fn items( wrap: &TextStream<TestSink, Vec<u8>> ) -> Vec<String>
+{
+	wrap.inner().items.iter().map( |i| String::from_utf8( i.clone() ).expect( "utf8" ) ).collect()
+}
+
+
+
+// "é" is 0xC3 0xA9.
+//
+#[ test ] fn split_code_point()
+{
+	let mut wrap = TextStream::new( TestSink::ok( 3 ) );
+
+	assert_matches!( write( &mut wrap, &[ b'a', 0xC3 ] ), Poll::Ready(Ok(2)) );
+	assert_matches!( write( &mut wrap, &[ 0xA9, b'b' ] ), Poll::Ready(Ok(2)) );
+
+	assert_eq!( items( &wrap ), vec![ "a", "éb" ] );
+}
+
+
+// Vectored writes are validated across buffers.
+//
+#[ test ] fn vectored()
+{
+	let mut wrap = TextStream::new( TestSink::ok( 1 ) );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+	let     bufs  = [ IoSlice::new( &[ b'a', 0xC3 ] ), IoSlice::new( &[ 0xA9 ] ), IoSlice::new( &[ 0xA9 ] ) ];
+
+	assert_matches!( Pin::new( &mut wrap ).poll_write_vectored( &mut cx, &bufs ), Poll::Ready(Ok(3)) );
+
+	assert_eq!( items( &wrap ), vec![ "aé" ] );
+}
+
+
+// Only the valid start of a write is taken.
+//
+#[ test ] fn invalid()
+{
+	let mut wrap = TextStream::new( TestSink::ok( 2 ) );
+
+	assert_matches!
+	(
+		write( &mut wrap, &[ 0xFF ] ),
+		Poll::Ready(Err(e)) => assert_eq!( e.kind(), io::ErrorKind::InvalidData )
+	);
+
+	assert_matches!( write( &mut wrap, &[ b'a', 0xFF, b'b' ] ), Poll::Ready(Ok(1)) );
+
+	assert_eq!( items( &wrap ), vec![ "a" ] );
+}
+
+
+// A code point can't be completed by an ascii character.
+//
+#[ test ] fn invalid_continuation()
+{
+	let mut wrap = TextStream::new( TestSink::ok( 2 ) );
+
+	assert_matches!( write( &mut wrap, &[ 0xC3 ] ), Poll::Ready(Ok(1)) );
+
+	assert_matches!
+	(
+		write( &mut wrap, b"a" ),
+		Poll::Ready(Err(e)) => assert_eq!( e.kind(), io::ErrorKind::InvalidData )
+	);
+
+	assert!( wrap.inner().items.is_empty() );
+}
+
+
+// "€" is 0xE2 0x82 0xAC.
+//
+#[ test ] fn write_buffer()
+{
+	let mut wrap = TextStream::new( TestSink::ok( 2 ) ).with_write_buffer( 4 );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	// The buffer is full, but only "ab" can be sent.
+	//
+	assert_matches!( write( &mut wrap, &[ b'a', b'b', 0xE2, 0x82 ] ), Poll::Ready(Ok(4)) );
+	assert_matches!( write( &mut wrap, &[ 0xAC                   ] ), Poll::Ready(Ok(1)) );
+
+	assert_eq!( items( &wrap ), vec![ "ab" ] );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_flush( &mut cx ), Poll::Ready(Ok(())) );
+
+	assert_eq!( items( &wrap ), vec![ "ab", "€" ] );
+}
+
+
+// The code point that doesn't fit is kept for the next item.
+//
+#[ test ] fn max_item_size()
+{
+	let mut wrap = TextStream::new( TestSink::ok( 2 ) ).with_max_item_size( 4 );
+
+	let text = "aaa€".as_bytes();
+
+	assert_matches!( write( &mut wrap, text       ), Poll::Ready(Ok(4)) );
+	assert_matches!( write( &mut wrap, &text[4..] ), Poll::Ready(Ok(2)) );
+
+	assert_eq!( items( &wrap ), vec![ "aaa", "€" ] );
+}
+
+
+// Nothing can be done with half a code point on close.
+//
+#[ test ] fn flush_close()
+{
+	let mut wrap = TextStream::new( TestSink::ok( 2 ) );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	assert_matches!( write( &mut wrap, &[ b'a', 0xC3 ] ), Poll::Ready(Ok(2)) );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_flush( &mut cx ), Poll::Ready(Ok(())) );
+
+	assert_matches!
+	(
+		Pin::new( &mut wrap ).poll_close( &mut cx ),
+		Poll::Ready(Err(e)) => assert_eq!( e.kind(), io::ErrorKind::InvalidData )
+	);
+
+	assert_matches!( Pin::new( &mut wrap ).poll_close( &mut cx ), Poll::Ready(Ok(())) );
+
+	assert_eq!( items( &wrap ), vec![ "a" ] );
+}
+
+
+// Not every character fits in 3 bytes.
+//
+#[ test ] #[ should_panic ] fn max_item_size_too_small()
+{
+	let _ = TextStream::<TestSink, Vec<u8>>::new( TestSink::ok( 0 ) ).with_max_item_size( 3 );
+}
+
+
+
+// A Sink<String> transport, which can't create items from bytes. The channel loops back, so what
+// we write is read back. Closing drops the sender, so the stream ends.
+//
+#[ test ] fn string_channel()
+{
+	let (tx, rx) = mpsc::channel::<String>( 8 );
+
+	let sink     = tx.sink_map_err( |_| io::Error::from( io::ErrorKind::NotConnected ) );
+	let mut wrap = TextStream::join( rx.map( Ok ), sink );
+
+	executor::block_on( async
+	{
+		wrap.write_all( &[ b'a', 0xC3 ] ).await.expect( "write" );
+		wrap.write_all( &[ 0xA9, b'b' ] ).await.expect( "write" );
+
+		wrap.close().await.expect( "close" );
+
+		let mut text = String::new();
+
+		wrap.read_to_string( &mut text ).await.expect( "read" );
+
+		assert_eq!( text, "aéb" );
+	});
+}
+
+
+// Arc<str> is AsRef<str>, but not AsRef<[u8]>.
+//
+#[ test ] fn read_str_items()
+{
+	let items: Vec< io::Result<Arc<str>> > = vec![ Ok( "hé".into() ), Ok( "llo".into() ) ];
+
+	let mut wrap = TextStream::new( stream::iter( items ) );
+	let mut text = String::new();
+
+	executor::block_on( async
+	{
+		wrap.read_to_string( &mut text ).await.expect( "read" );
+	});
+
+	assert_eq!( text, "héllo" );
+}
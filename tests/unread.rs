@@ -0,0 +1,107 @@
+// Test IoStream::unread:
+//
+// ✔ poll_fill_buf returns unread bytes first
+// ✔ poll_read continues with the current item after the unread bytes
+// ✔ poll_read_vectored returns unread bytes first
+// ✔ unread bytes come before the end of the stream
+// ✔ unreading nothing does nothing
+//
+mod common;
+
+use
+{
+	common            :: { *                                                 } ,
+	async_io_stream   :: { *                                                 } ,
+	futures           :: { *, task::noop_waker                               } ,
+	std               :: { task::{ Poll, Context }, pin::Pin, io::IoSliceMut } ,
+	pretty_assertions :: { assert_eq                                         } ,
+	assert_matches    :: { assert_matches                                    } ,
+};
+
+
+
+#[ test ] fn fill_buf()
+{
+	let actions = vec![ vec![ 1, 2, 3 ].into(), vec![ 4 ].into() ];
+
+	let mut wrap = IoStream::new( TestStream::new( actions.into() ) );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_fill_buf( &mut cx ), Poll::Ready(Ok(data)) => assert_eq!( data, &[ 1, 2, 3 ] ) );
+
+	Pin::new( &mut wrap ).consume( 3 );
+	wrap.unread( &[ 2, 3 ] );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_fill_buf( &mut cx ), Poll::Ready(Ok(data)) => assert_eq!( data, &[ 2, 3 ] ) );
+
+	Pin::new( &mut wrap ).consume( 2 );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_fill_buf( &mut cx ), Poll::Ready(Ok(data)) => assert_eq!( data, &[ 4 ] ) );
+}
+
+
+
+#[ test ] fn read_current_item()
+{
+	let mut wrap = IoStream::new( TestStream::new( vec![ vec![ 1, 2, 3, 4 ].into() ].into() ) );
+
+	assert_matches!( read( &mut wrap, 2 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 1, 2 ] ) );
+
+	wrap.unread( &[ 9 ] );
+
+	assert_matches!( read( &mut wrap, 4 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 9, 3, 4 ] ) );
+}
+
+
+
+#[ test ] fn read_vectored()
+{
+	let mut wrap = IoStream::new( TestStream::new( vec![ vec![ 3, 4 ].into() ].into() ) );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+	let mut a     = [ 0u8; 1 ];
+	let mut b     = [ 0u8; 3 ];
+
+	wrap.unread( &[ 1, 2 ] );
+
+	let mut bufs = [ IoSliceMut::new( &mut a ), IoSliceMut::new( &mut b ) ];
+
+	assert_matches!( Pin::new( &mut wrap ).poll_read_vectored( &mut cx, &mut bufs ), Poll::Ready(Ok(4)) );
+
+	assert_eq!( a, [ 1       ] );
+	assert_eq!( b, [ 2, 3, 4 ] );
+}
+
+
+
+#[ test ] fn eof()
+{
+	let mut wrap = IoStream::new( TestStream::new( vec![ vec![ 1 ].into() ].into() ) );
+
+	assert_matches!( read( &mut wrap, 2 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 1 ] ) );
+	assert_matches!( read( &mut wrap, 2 ), Poll::Ready(Ok(data)) => assert!( data.is_empty() ) );
+
+	wrap.unread( &[ 1 ] );
+
+	assert_matches!( read( &mut wrap, 2 ), Poll::Ready(Ok(data)) => assert_eq!( data, vec![ 1 ] ) );
+	assert_matches!( read( &mut wrap, 2 ), Poll::Ready(Ok(data)) => assert!( data.is_empty() ) );
+}
+
+
+
+#[ test ] fn nothing()
+{
+	let mut wrap = IoStream::new( TestStream::new( vec![ vec![ 1 ].into() ].into() ) );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	wrap.unread( &[] );
+
+	assert_eq!( wrap.state(), IoState::default() );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_fill_buf( &mut cx ), Poll::Ready(Ok(data)) => assert_eq!( data, &[ 1 ] ) );
+}
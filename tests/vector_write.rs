@@ -9,6 +9,7 @@
 // ✔ return error from poll_flush on next call
 // ✔ return error from poll_flush on next call to poll_write
 // - don't wake up waker from sink
+// ✔ tokio impl creates one item from all buffers as well
 //
 mod common;
 
@@ -53,6 +54,28 @@ fn tester( ra: Vec<ReadyAction>, sa: Vec<SendAction>, fa: Vec<FlushAction>, data
 }
 
 
+#[ cfg( feature = "tokio_io" ) ]
+//
+fn tester_tokio( ra: Vec<ReadyAction>, sa: Vec<SendAction>, fa: Vec<FlushAction>, data: Vec<Vec<u8>> )
+
+	-> ( IoStream<TestSink, Vec<u8>>, Poll<io::Result<usize>> )
+{
+	let     sink = TestSink::new( ra, sa, fa );
+	let mut wrap = IoStream::new( sink );
+
+	let bufs: Vec< IoSlice<'_> > = data.iter().map( |d| IoSlice::new( d ) ).collect();
+
+	let waker  = noop_waker();
+	let mut cx = Context::from_waker( &waker );
+
+	assert!( tokio::io::AsyncWrite::is_write_vectored( &wrap ) );
+
+	let out = tokio::io::AsyncWrite::poll_write_vectored( Pin::new( &mut wrap ), &mut cx, &bufs );
+
+	(wrap, out)
+}
+
+
 // Return pending from poll_ready.
 //
 #[ test ] fn poll_ready_pending()
@@ -123,6 +146,30 @@ fn tester( ra: Vec<ReadyAction>, sa: Vec<SendAction>, fa: Vec<FlushAction>, data
 }
 
 
+// Normal use case, 2 buffers to one write.
+//
+#[ cfg( feature = "tokio_io" ) ]
+//
+#[ test ] fn normal_use_tokio()
+{
+	let ra = vec![ ReadyAction::Ok ];
+	let sa = vec![ SendAction::Ok  ];
+	let fa = vec![ FlushAction::Ok ];
+
+	let data = vec![ vec![ 1, 1 ], vec![ 2, 2 ] ];
+
+	let (wrap, out) = tester_tokio( ra, sa, fa, data );
+
+	assert_matches!( out, Poll::Ready( Ok(n) ) => assert_eq!( n, 4 ) );
+
+	assert_eq!( wrap.inner().poll_ready , 1                  );
+	assert_eq!( wrap.inner().start_send , 1                  );
+	assert_eq!( wrap.inner().poll_flush , 1                  );
+	assert_eq!( wrap.inner().items.len(), 1                  );
+	assert_eq!( wrap.inner().items[0]   , vec![ 1, 1, 2, 2 ] );
+}
+
+
 // Return errors from start_send.
 //
 #[ test ] fn send_error()
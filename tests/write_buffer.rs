@@ -0,0 +1,138 @@
+// Test IoStream::with_write_buffer:
+//
+// ✔ small writes are accumulated into one item
+// ✔ the buffer is sent as soon as it's full
+// ✔ poll_write returns pending while a full buffer can't be sent
+// ✔ a write that fills an empty buffer by itself is sent directly
+// ✔ flush and close send the buffer
+// ✔ vectored writes are buffered as well
+// ✔ into_parts returns buffered data
+//
+mod common;
+
+use
+{
+	common            :: { *                                              } ,
+	async_io_stream   :: { *                                              } ,
+	futures           :: { *, task::noop_waker                            } ,
+	std               :: { task::{ Poll, Context }, pin::Pin, io::IoSlice } ,
+	pretty_assertions :: { assert_eq                                      } ,
+	assert_matches    :: { assert_matches                                 } ,
+};
+
+
+fn buffered( capacity: usize, ra: Vec<ReadyAction> ) -> IoStream<TestSink, Vec<u8>>
+{
+	let sa = vec![ SendAction::Ok ; ra.len() ];
+	let fa = vec![ FlushAction::Ok; ra.len() * 2 ];
+
+	IoStream::new( TestSink::new( ra, sa, fa ) ).with_write_buffer( capacity )
+}
+
+
+
+// Accumulate small writes.
+//
+#[ test ] fn accumulate()
+{
+	let mut wrap = buffered( 4, vec![ ReadyAction::Ok ] );
+
+	assert_matches!( write( &mut wrap, &[ 1    ] ), Poll::Ready(Ok(1)) );
+	assert_matches!( write( &mut wrap, &[ 2, 3 ] ), Poll::Ready(Ok(2)) );
+
+	assert_eq!( wrap.inner().poll_ready , 0 );
+	assert_eq!( wrap.inner().items.len(), 0 );
+
+	// Only one byte fits, after which the buffer is full and sent.
+	//
+	assert_matches!( write( &mut wrap, &[ 4, 5 ] ), Poll::Ready(Ok(1)) );
+
+	assert_eq!( wrap.inner().items, vec![ vec![ 1, 2, 3, 4 ] ] );
+}
+
+
+// The sink is not ready when the buffer becomes full.
+//
+#[ test ] fn backpressure()
+{
+	let mut wrap = buffered( 2, vec![ ReadyAction::Pending, ReadyAction::Pending, ReadyAction::Ok ] );
+
+	assert_matches!( write( &mut wrap, &[ 1    ] ), Poll::Ready(Ok(1)) );
+	assert_matches!( write( &mut wrap, &[ 2, 3 ] ), Poll::Ready(Ok(1)) ); // full, sink pending
+	assert_matches!( write( &mut wrap, &[ 3    ] ), Poll::Pending      ); // full, sink pending
+	assert_matches!( write( &mut wrap, &[ 3    ] ), Poll::Ready(Ok(1)) ); // sent, buffered 3
+
+	assert_eq!( wrap.inner().poll_ready, 3                    );
+	assert_eq!( wrap.inner().items     , vec![ vec![ 1, 2 ] ] );
+}
+
+
+// Writes that fill the buffer by themselves are sent directly.
+//
+#[ test ] fn large_write()
+{
+	let mut wrap = buffered( 2, vec![ ReadyAction::Ok ] );
+
+	assert_matches!( write( &mut wrap, &[ 1, 2, 3 ] ), Poll::Ready(Ok(3)) );
+
+	assert_eq!( wrap.inner().items, vec![ vec![ 1, 2, 3 ] ] );
+}
+
+
+// Flush and close send the partial buffer.
+//
+#[ test ] fn flush_close()
+{
+	let mut wrap = buffered( 8, vec![ ReadyAction::Ok, ReadyAction::Ok ] );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	assert_matches!( write( &mut wrap, &[ 1, 2 ] ), Poll::Ready(Ok(2)) );
+	assert_matches!( Pin::new( &mut wrap ).poll_flush( &mut cx ), Poll::Ready(Ok(())) );
+
+	assert_matches!( write( &mut wrap, &[ 3 ] ), Poll::Ready(Ok(1)) );
+	assert_matches!( Pin::new( &mut wrap ).poll_close( &mut cx ), Poll::Ready(Ok(())) );
+
+	assert_eq!( wrap.inner().items, vec![ vec![ 1, 2 ], vec![ 3 ] ] );
+}
+
+
+// Vectored writes fill the buffer with as much as fits.
+//
+#[ test ] fn vectored()
+{
+	let mut wrap = buffered( 4, vec![ ReadyAction::Ok ] );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+	let     bufs  = [ IoSlice::new( &[ 2 ] ), IoSlice::new( &[ 3, 4, 5 ] ) ];
+
+	assert_matches!( write( &mut wrap, &[ 1 ] ), Poll::Ready(Ok(1)) );
+	assert_matches!( Pin::new( &mut wrap ).poll_write_vectored( &mut cx, &bufs ), Poll::Ready(Ok(3)) );
+
+	assert_eq!( wrap.inner().items, vec![ vec![ 1, 2, 3, 4 ] ] );
+}
+
+
+// Buffered data is part of IoParts and sent by the restored IoStream, even without a buffer.
+//
+#[ test ] fn parts()
+{
+	let mut wrap = buffered( 8, vec![ ReadyAction::Ok ] );
+
+	assert_matches!( write( &mut wrap, &[ 1, 2 ] ), Poll::Ready(Ok(2)) );
+
+	let parts = wrap.into_parts();
+
+	assert_eq!( parts.write_buf, vec![ 1, 2 ] );
+
+	let mut wrap: IoStream<TestSink, Vec<u8>> = IoStream::from_parts( parts );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	assert_matches!( Pin::new( &mut wrap ).poll_flush( &mut cx ), Poll::Ready(Ok(())) );
+
+	assert_eq!( wrap.inner().items, vec![ vec![ 1, 2 ] ] );
+}